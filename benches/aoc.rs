@@ -1,14 +1,17 @@
 use aoc::{
     PuzzleId,
-    storage::{self},
+    storage::{EncryptedStore, PuzzleStore},
 };
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use std::env;
+use std::{env, path::Path};
 
 fn bench_puzzle(c: &mut Criterion, year: u32, day: u32, part: u32) {
     let puzzle_id = PuzzleId::new(year, day, part);
     let password = env::var("AOC_KEY").unwrap();
-    let puzzle = storage::get(&puzzle_id, &password).unwrap();
+    let puzzle = EncryptedStore::unlock(Path::new(env!("CARGO_MANIFEST_DIR")), &password)
+        .unwrap_or_else(|e| panic!("{e}"))
+        .get(&puzzle_id)
+        .unwrap();
 
     let input = &puzzle.inputs.first().unwrap().input;
     c.bench_with_input(
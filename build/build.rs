@@ -21,12 +21,13 @@ pub struct PuzzleId {
 
 fn main() -> io::Result<()> {
     let password = env::var("AOC_KEY").unwrap();
+    let bound = cipher::verify_key(&password).unwrap_or_else(|e| panic!("{e}"));
 
     write("solvers.rs", &solvers::generate())?;
     write("tests.rs", &tests::generate())?;
     write("benchmarks.rs", &benchmarks::generate())?;
 
-    docs::generate(&password)?;
+    docs::generate(&password, bound)?;
 
     Ok(())
 }
@@ -1,37 +1,189 @@
 use aes_gcm_siv::{
     Aes256GcmSiv, KeyInit, Nonce,
-    aead::{Aead, generic_array::GenericArray},
+    aead::{Aead, Payload, generic_array::GenericArray},
 };
+use argon2::{Algorithm, Argon2, Params, Version};
 use blake3::Hasher;
-use std::{fs, path::Path};
+use std::{collections::BTreeMap, fs, path::Path, sync::Mutex};
+use walkdir::WalkDir;
 
 const SALT_LENGTH: usize = 32;
 const NONCE_LENGTH: usize = 12;
 const KEY_LENGTH: usize = 32;
 
-pub fn decrypt_file(path: &Path, password: &str) -> Result<String, Box<dyn std::error::Error>> {
+/// The container format of `src/storage/cipher.rs`: an optional header of magic, version and KDF,
+/// followed by the salt, nonce and ciphertext. From version 2 the path of the file relative to
+/// the store is authenticated as associated data, and from version 3 each file is encrypted with
+/// a subkey for its path.
+const MAGIC: &[u8; 4] = b"AOCE";
+const VERSION: u8 = 3;
+const BOUND_VERSION: u8 = 2;
+const SUBKEY_VERSION: u8 = 3;
+const STORE_DIR: &str = "puzzles.enc";
+const KDF_BLAKE3: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+/// The limits on Argon2id memory in KiB, iterations and parallelism.
+const MAX_ARGON2_PARAMS: [u32; 3] = [1024 * 1024, 10, 16];
+
+/// Keys already derived, by KDF and salt, as files of a store share the salt of its record.
+static KEYS: Mutex<BTreeMap<Vec<u8>, [u8; KEY_LENGTH]>> = Mutex::new(BTreeMap::new());
+
+/// The key check record of the store and its known plaintext, see `EncryptedStore::verify_key`.
+const KEY_CHECK: &str = "puzzles.enc/key.check.enc";
+const KEY_CHECK_CONTENTS: &str = "Advent of Code";
+
+/// Fails if the password does not decrypt the key check record of the store, or any of its files
+/// if it has no record yet. Returns whether files must authenticate their path, following the
+/// rules of `EncryptedStore::unlock`.
+pub fn verify_key(password: &str) -> Result<bool, String> {
+    let record = Path::new(KEY_CHECK);
+    if record.exists() {
+        return match decrypt_file(record, password, false) {
+            Ok(contents) if contents == KEY_CHECK_CONTENTS => Ok(is_bound(record)),
+            _ => Err("AOC_KEY does not match this store".to_string()),
+        };
+    }
+
+    let files: Vec<_> = WalkDir::new(STORE_DIR)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "enc")
+        })
+        .map(walkdir::DirEntry::into_path)
+        .collect();
+    if files.iter().any(|file| is_bound(file)) {
+        return Err(format!("The key check record {KEY_CHECK} is missing"));
+    }
+    match files.first() {
+        Some(file) if decrypt_file(file, password, false).is_err() => {
+            Err("AOC_KEY does not match this store".to_string())
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Whether a file authenticates its path.
+fn is_bound(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|encrypted| {
+        encrypted
+            .strip_prefix(MAGIC)
+            .and_then(|rest| rest.first())
+            .is_some_and(|&version| version >= BOUND_VERSION)
+    })
+}
+
+/// Decrypts a file of the store. Files in older formats are refused if `bound`, as they could
+/// have been put in place of others.
+pub fn decrypt_file(
+    path: &Path,
+    password: &str,
+    bound: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
     let encrypted = fs::read(path)?;
+    if bound && !is_bound(path) {
+        return Err("File in an older format in a store that authenticates paths".into());
+    }
+
+    let mut version = 0;
+    let (kdf, header) = match encrypted.strip_prefix(MAGIC) {
+        Some(rest) => {
+            version = *rest.first().ok_or("File too short")?;
+            if version == 0 || version > VERSION {
+                return Err("Unsupported format version".into());
+            }
+            let kdf = &rest[1..];
+            let length = match kdf.first() {
+                Some(&KDF_BLAKE3) => 1,
+                Some(&KDF_ARGON2ID) => 13,
+                _ => return Err("Unsupported key derivation".into()),
+            };
+            (
+                kdf.get(..length).ok_or("File too short")?,
+                MAGIC.len() + 1 + length,
+            )
+        }
+        None => (&[KDF_BLAKE3][..], 0),
+    };
 
-    if encrypted.len() < SALT_LENGTH + NONCE_LENGTH {
+    if encrypted.len() < header + SALT_LENGTH + NONCE_LENGTH {
         return Err("File too short".into());
     }
 
-    let salt = &encrypted[0..SALT_LENGTH];
-    let nonce = &encrypted[SALT_LENGTH..SALT_LENGTH + NONCE_LENGTH];
-    let ciphertext = &encrypted[SALT_LENGTH + NONCE_LENGTH..];
+    let salt = &encrypted[header..header + SALT_LENGTH];
+    let nonce = &encrypted[header + SALT_LENGTH..header + SALT_LENGTH + NONCE_LENGTH];
+    let ciphertext = &encrypted[header + SALT_LENGTH + NONCE_LENGTH..];
 
-    let key = derive_key(password.as_bytes(), salt);
+    let aad = if version >= BOUND_VERSION {
+        associated_data(path)
+    } else {
+        Vec::new()
+    };
+    let mut key = derive_key(kdf, password.as_bytes(), salt)?;
+    if version >= SUBKEY_VERSION {
+        let mut hasher = Hasher::new_derive_key("aoc 2026-10-18 file key");
+        hasher.update(&key);
+        hasher.update(&aad);
+        key = *hasher.finalize().as_bytes();
+    }
     let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&key));
     let plaintext = cipher
-        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &aad,
+            },
+        )
         .map_err(|e| format!("Decryption failed: {e}"))?;
 
     String::from_utf8(plaintext).map_err(Into::into)
 }
 
-fn derive_key(password: &[u8], salt: &[u8]) -> [u8; KEY_LENGTH] {
-    let mut hasher = Hasher::new();
-    hasher.update(password);
-    hasher.update(salt);
-    *hasher.finalize().as_bytes()
+fn derive_key(
+    kdf: &[u8],
+    password: &[u8],
+    salt: &[u8],
+) -> Result<[u8; KEY_LENGTH], Box<dyn std::error::Error>> {
+    let id = [kdf, salt].concat();
+    let mut keys = KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(key) = keys.get(&id) {
+        return Ok(*key);
+    }
+
+    let mut key = [0u8; KEY_LENGTH];
+    if kdf[0] == KDF_ARGON2ID {
+        let param = |index: usize| {
+            let start = 1 + 4 * index;
+            u32::from_le_bytes(kdf[start..start + 4].try_into().unwrap())
+        };
+        if (0..3).any(|index| param(index) > MAX_ARGON2_PARAMS[index]) {
+            return Err("Key derivation parameters exceed the limits".into());
+        }
+        let params = Params::new(param(0), param(1), param(2), Some(KEY_LENGTH))
+            .map_err(|e| format!("Invalid key derivation parameters: {e}"))?;
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password, salt, &mut key)
+            .map_err(|e| format!("Key derivation failed: {e}"))?;
+    } else {
+        let mut hasher = Hasher::new();
+        hasher.update(password);
+        hasher.update(salt);
+        key = *hasher.finalize().as_bytes();
+    }
+    keys.insert(id, key);
+    Ok(key)
+}
+
+/// The path of a file relative to the store, without the extension of encrypted files.
+fn associated_data(path: &Path) -> Vec<u8> {
+    let relative = path.strip_prefix(STORE_DIR).unwrap_or(path);
+    relative
+        .with_extension("")
+        .iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+        .into_bytes()
 }
@@ -2,16 +2,16 @@ use crate::cipher::decrypt_file;
 use crate::{PuzzleId, discovery::discover_docs, write};
 use std::{io, path::PathBuf};
 
-pub fn generate(password: &str) -> io::Result<()> {
+pub fn generate(password: &str, bound: bool) -> io::Result<()> {
     println!("cargo:rerun-if-changed=puzzles.enc");
     let docs = discover_docs();
-    generate_docs(password, &docs)
+    generate_docs(password, bound, &docs)
 }
 
-fn generate_docs(password: &str, docs: &[PuzzleId]) -> io::Result<()> {
+fn generate_docs(password: &str, bound: bool, docs: &[PuzzleId]) -> io::Result<()> {
     for id in docs {
         let path = to_path(id);
-        if let Ok(html) = decrypt_file(&path, password) {
+        if let Ok(html) = decrypt_file(&path, password, bound) {
             let filename = format!("aoc_{}_{:02}_{}.html", id.year, id.day, id.part);
             write(&filename, &html)?;
         }
@@ -0,0 +1,54 @@
+use regex::{Captures, Regex};
+use reqwest::Url;
+
+/// Resolves a reference, such as the `src` of an image or a `url(...)` in a stylesheet, against
+/// the URL of the page or stylesheet it appears in.
+#[must_use]
+pub fn resolve_url(base: &str, reference: &str) -> Option<String> {
+    Url::parse(base)
+        .ok()?
+        .join(reference)
+        .ok()
+        .map(String::from)
+}
+
+/// Makes the `url(...)` and `@import` references of a stylesheet absolute, resolving them
+/// against the URL of the stylesheet so that they keep working wherever its text ends up.
+#[must_use]
+pub fn resolve_css_urls(css: &str, base: &str) -> String {
+    let import = Regex::new(r#"(@import\s+['"])([^'"]+)(['"])"#).unwrap();
+    let url = Regex::new(r#"(url\(\s*['"]?)([^'")]+)(['"]?\s*\))"#).unwrap();
+
+    let resolve = |caps: &Captures| {
+        if caps[2].starts_with("data:") || caps[2].starts_with('#') {
+            return caps[0].to_string();
+        }
+        resolve_url(base, &caps[2]).map_or_else(
+            || caps[0].to_string(),
+            |resolved| format!("{}{resolved}{}", &caps[1], &caps[3]),
+        )
+    };
+    let css = import.replace_all(css, resolve);
+    url.replace_all(&css, resolve).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_against_the_stylesheet() {
+        let base = "https://adventofcode.com/static/style.css?31";
+        let css = "@import 'fonts.css';\n\
+                   @font-face { src: url(\"font.woff2\"); }\n\
+                   a { background: url(/img/a.png); }\n\
+                   b { background: url(data:image/png;base64,AA==); }";
+        assert_eq!(
+            resolve_css_urls(css, base),
+            "@import 'https://adventofcode.com/static/fonts.css';\n\
+             @font-face { src: url(\"https://adventofcode.com/static/font.woff2\"); }\n\
+             a { background: url(https://adventofcode.com/img/a.png); }\n\
+             b { background: url(data:image/png;base64,AA==); }"
+        );
+    }
+}
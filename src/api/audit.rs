@@ -0,0 +1,37 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// An append-only log of every request sent to the server.
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    #[must_use]
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Appends a line with the time, method, URL and response status, or `-` if none was received.
+    pub fn record(&self, method: &str, url: &str, status: Option<u16>) -> Result<(), io::Error> {
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(io::Error::other)?;
+        let status = status.map_or("-".to_string(), |status| status.to_string());
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{timestamp} {method} {url} {status}")
+    }
+}
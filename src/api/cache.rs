@@ -0,0 +1,74 @@
+use crate::storage::SaltedKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use time::OffsetDateTime;
+
+/// A cached response body together with the validators needed to revalidate it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched_at: OffsetDateTime,
+    pub body: String,
+}
+
+impl CachedResponse {
+    #[must_use]
+    pub fn is_fresh(&self, max_age: Duration) -> bool {
+        self.fetched_at + max_age > OffsetDateTime::now_utc()
+    }
+}
+
+/// A disk-backed cache of responses, keyed by URL.
+///
+/// Responses contain puzzle text and private leaderboards, so entries are encrypted like
+/// `puzzles.enc`, bound to their URL.
+pub struct HttpCache {
+    dir: PathBuf,
+    password: String,
+}
+
+impl HttpCache {
+    #[must_use]
+    pub fn new(dir: &Path, password: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            password: password.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        let encrypted = fs::read(self.entry_path(url)).ok()?;
+        let decrypted = SaltedKey::extract(&self.password, &encrypted)
+            .and_then(|key| key.decrypt(&encrypted, url.as_bytes()))
+            .ok()?;
+        toml::from_str(&String::from_utf8(decrypted).ok()?).ok()
+    }
+
+    pub fn put(&self, response: &CachedResponse) -> Result<(), io::Error> {
+        let contents = toml::to_string(response).map_err(io::Error::other)?;
+        let encrypted = SaltedKey::new(&self.password)
+            .encrypt(contents.as_bytes(), response.url.as_bytes())
+            .map_err(|_| io::Error::other("failed to encrypt cache entry"))?;
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(&response.url), encrypted)?;
+
+        // Entries used to be written in plaintext
+        let legacy_path = self.entry_path(&response.url).with_extension("toml");
+        if legacy_path.exists() {
+            fs::remove_file(legacy_path)?;
+        }
+        Ok(())
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let key = blake3::hash(url.as_bytes()).to_hex();
+        self.dir.join(format!("{key}.enc"))
+    }
+}
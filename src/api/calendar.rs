@@ -0,0 +1,132 @@
+use super::ApiError;
+use scraper::{Html, Selector};
+
+/// The number of stars collected in an event, as listed on `/events`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventStars {
+    pub year: u32,
+    pub stars: u32,
+}
+
+/// The number of stars collected on a day, as shown on the `/{year}` calendar.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DayStars {
+    pub year: u32,
+    pub day: u32,
+    pub stars: u32,
+}
+
+pub(super) fn parse_events(html: &str) -> Result<Vec<EventStars>, ApiError> {
+    let document = Html::parse_document(html);
+    let event_selector = Selector::parse(".eventlist-event").unwrap();
+    let link_selector = Selector::parse("a").unwrap();
+    let stars_selector = Selector::parse(".star-count").unwrap();
+
+    let events: Vec<_> = document
+        .select(&event_selector)
+        .filter_map(|event| {
+            let year = event
+                .select(&link_selector)
+                .next()?
+                .text()
+                .collect::<String>()
+                .trim_matches(|c| c == '[' || c == ']')
+                .parse()
+                .ok()?;
+            let stars = event.select(&stars_selector).next().map_or(0, |count| {
+                count
+                    .text()
+                    .collect::<String>()
+                    .trim()
+                    .trim_end_matches('*')
+                    .parse()
+                    .unwrap_or(0)
+            });
+            Some(EventStars { year, stars })
+        })
+        .collect();
+
+    if events.is_empty() {
+        return Err(ApiError::ParseError);
+    }
+    Ok(events)
+}
+
+pub(super) fn parse_calendar(html: &str, year: u32) -> Result<Vec<DayStars>, ApiError> {
+    let document = Html::parse_document(html);
+    let day_selector = Selector::parse(".calendar a[href]").unwrap();
+    let prefix = format!("/{year}/day/");
+
+    let mut days: Vec<_> = document
+        .select(&day_selector)
+        .filter_map(|link| {
+            let day = link
+                .value()
+                .attr("href")?
+                .strip_prefix(&prefix)?
+                .parse()
+                .ok()?;
+            let label = link.value().attr("aria-label").unwrap_or_default();
+            let classes: Vec<_> = link.value().classes().collect();
+
+            let stars = if label.contains("two stars") || classes.contains(&"calendar-verycomplete")
+            {
+                2
+            } else if label.contains("one star") || classes.contains(&"calendar-complete") {
+                1
+            } else {
+                0
+            };
+            Some(DayStars { year, day, stars })
+        })
+        .collect();
+
+    if days.is_empty() {
+        return Err(ApiError::ParseError);
+    }
+    days.sort_by_key(|d| d.day);
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_events() {
+        let html = r#"<main>
+            <div class="eventlist-event"><a href="/2025">[2025]</a> <span class="star-count">3*</span></div>
+            <div class="eventlist-event"><a href="/2024">[2024]</a></div>
+        </main>"#;
+
+        assert_eq!(
+            parse_events(html).unwrap(),
+            [
+                EventStars {
+                    year: 2025,
+                    stars: 3
+                },
+                EventStars {
+                    year: 2024,
+                    stars: 0
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_calendar() {
+        let html = r#"<main><pre class="calendar">
+            <a aria-label="Day 2, one star" href="/2024/day/2" class="calendar-day2 calendar-complete">2</a>
+            <a aria-label="Day 1, two stars" href="/2024/day/1" class="calendar-day1 calendar-verycomplete">1</a>
+            <a href="/2024/day/3" class="calendar-day3">3</a>
+        </pre></main>"#;
+
+        let stars: Vec<_> = parse_calendar(html, 2024)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.day, d.stars))
+            .collect();
+        assert_eq!(stars, [(1, 2), (2, 1), (3, 0)]);
+    }
+}
@@ -0,0 +1,233 @@
+use super::ApiError;
+use crate::storage::SaltedKey;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use reqwest::{
+    blocking::{Request, Response},
+    header,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// A recorded request and the response it received.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    url: String,
+    #[serde(default)]
+    request_body: Option<String>,
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    #[serde(default)]
+    content_type: Option<String>,
+    /// The response body, base64 encoded so that fonts and images survive. Tapes without a
+    /// version stored it as text.
+    body: String,
+}
+
+/// The tape format written by this version of the code.
+const TAPE_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Tape {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+/// Records every request and response to an encrypted file, or replays them without network.
+///
+/// Cassettes contain puzzle text, so they are encrypted like `puzzles.enc`. Requests are replayed
+/// in the order they were recorded, each matched by method and URL.
+pub struct Cassette {
+    path: PathBuf,
+    password: String,
+    mode: Mode,
+    tape: Mutex<Tape>,
+    /// How many interactions of the tape have been replayed, by index.
+    replayed: Mutex<Vec<bool>>,
+}
+
+impl Cassette {
+    /// Starts a new cassette. The file is replaced once recording is done, when the cassette is
+    /// finished or dropped.
+    #[must_use]
+    pub fn record(path: &Path, password: &str) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            password: password.to_string(),
+            mode: Mode::Record,
+            tape: Mutex::new(Tape {
+                version: TAPE_VERSION,
+                interactions: Vec::new(),
+            }),
+            replayed: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn replay(path: &Path, password: &str) -> Result<Self, ApiError> {
+        let encrypted = fs::read(path)?;
+        let decrypted = SaltedKey::extract(password, &encrypted)
+            .and_then(|key| key.decrypt(&encrypted, &[]))
+            .map_err(|_| ApiError::Cassette(format!("failed to decrypt {}", path.display())))?;
+        let tape: Tape = String::from_utf8(decrypted)
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .ok_or_else(|| ApiError::Cassette(format!("failed to parse {}", path.display())))?;
+
+        let replayed = vec![false; tape.interactions.len()];
+        Ok(Self {
+            path: path.to_path_buf(),
+            password: password.to_string(),
+            mode: Mode::Replay,
+            tape: Mutex::new(tape),
+            replayed: Mutex::new(replayed),
+        })
+    }
+
+    /// The method and URL of every recorded interaction, in the order they were recorded.
+    #[must_use]
+    pub fn requests(&self) -> Vec<(String, String)> {
+        self.tape
+            .lock()
+            .unwrap()
+            .interactions
+            .iter()
+            .map(|interaction| (interaction.method.clone(), interaction.url.clone()))
+            .collect()
+    }
+
+    pub(super) fn is_replaying(&self) -> bool {
+        self.mode == Mode::Replay
+    }
+
+    /// Answers a request with the first matching interaction that has not been replayed yet.
+    pub(super) fn play(&self, request: &Request) -> Result<Response, ApiError> {
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+
+        let tape = self.tape.lock().unwrap();
+        let mut replayed = self.replayed.lock().unwrap();
+        let index = tape
+            .interactions
+            .iter()
+            .enumerate()
+            .position(|(index, interaction)| {
+                !replayed[index] && interaction.method == method && interaction.url == url
+            })
+            .ok_or_else(|| {
+                ApiError::Cassette(format!("no recorded response for {method} {url}"))
+            })?;
+        replayed[index] = true;
+
+        to_response(&tape.interactions[index], tape.version)
+    }
+
+    /// Saves a request and its response, returning an equivalent response to use in its place.
+    pub(super) fn record_response(
+        &self,
+        method: &str,
+        url: &str,
+        request_body: Option<String>,
+        response: Response,
+    ) -> Result<Response, ApiError> {
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| **name != header::CONTENT_TYPE)
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+        let body = STANDARD.encode(response.bytes()?);
+
+        let interaction = Interaction {
+            method: method.to_string(),
+            url: url.to_string(),
+            request_body,
+            status,
+            headers,
+            content_type,
+            body,
+        };
+        let response = to_response(&interaction, TAPE_VERSION)?;
+
+        self.tape.lock().unwrap().interactions.push(interaction);
+        Ok(response)
+    }
+
+    /// Writes the recorded interactions to the cassette file. Recording cassettes are also saved
+    /// when they are dropped, so this is only needed to handle errors.
+    pub fn finish(&self) -> Result<(), ApiError> {
+        if self.mode == Mode::Record {
+            self.save(&self.tape.lock().unwrap())?;
+        }
+        Ok(())
+    }
+
+    fn save(&self, tape: &Tape) -> Result<(), ApiError> {
+        let text = toml::to_string(tape)
+            .map_err(|_| ApiError::Cassette("failed to serialize cassette".to_string()))?;
+        let encrypted = SaltedKey::new(&self.password)
+            .encrypt(text.as_bytes(), &[])
+            .map_err(|_| ApiError::Cassette("failed to encrypt cassette".to_string()))?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, encrypted)?;
+        Ok(())
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            eprintln!("Failed to save cassette {}: {e}", self.path.display());
+        }
+    }
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+fn to_response(interaction: &Interaction, version: u32) -> Result<Response, ApiError> {
+    let invalid = || ApiError::Cassette("invalid recorded response".to_string());
+
+    let mut builder = http::Response::builder().status(interaction.status);
+    for (name, value) in &interaction.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(content_type) = &interaction.content_type {
+        builder = builder.header(header::CONTENT_TYPE, content_type);
+    }
+    let body = if version == 0 {
+        interaction.body.clone().into_bytes()
+    } else {
+        STANDARD.decode(&interaction.body).map_err(|_| invalid())?
+    };
+    let response = builder.body(body).map_err(|_| invalid())?;
+    Ok(Response::from(response))
+}
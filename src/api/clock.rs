@@ -0,0 +1,124 @@
+use crate::PuzzleId;
+use std::{fmt, sync::Mutex, thread, time::Duration};
+use time::{Date, Month, OffsetDateTime, Time, UtcOffset};
+
+/// A source of the current time, which can be replaced to test unlock behaviour outside of
+/// December.
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> OffsetDateTime;
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// A clock starting at a given time, where sleeping advances the time instantly.
+#[derive(Debug)]
+pub struct SimulatedClock {
+    now: Mutex<OffsetDateTime>,
+}
+
+impl SimulatedClock {
+    #[must_use]
+    pub fn new(start: OffsetDateTime) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+/// Puzzles unlock at midnight EST, which Advent of Code uses all year.
+fn est() -> UtcOffset {
+    UtcOffset::from_hms(-5, 0, 0).unwrap()
+}
+
+/// The last day of an event, which is shorter from 2025 on.
+fn last_day(year: u32) -> u32 {
+    if year >= 2025 { 12 } else { 25 }
+}
+
+/// When the given day unlocks.
+#[must_use]
+pub fn unlock_time(year: u32, day: u32) -> OffsetDateTime {
+    Date::from_calendar_date(
+        year.cast_signed(),
+        Month::December,
+        u8::try_from(day).unwrap(),
+    )
+    .unwrap()
+    .with_time(Time::MIDNIGHT)
+    .assume_offset(est())
+}
+
+/// Whether the puzzle's day has unlocked at the given time.
+#[must_use]
+pub fn is_day_unlocked(id: &PuzzleId, now: OffsetDateTime) -> bool {
+    now >= unlock_time(id.year, id.day)
+}
+
+/// The year and day of the first puzzle unlocking after the given time.
+#[must_use]
+pub fn next_unlock(now: OffsetDateTime) -> (u32, u32) {
+    let now = now.to_offset(est());
+    let year = now.year().cast_unsigned();
+
+    if now.month() != Month::December {
+        return (year, 1);
+    }
+    let day = u32::from(now.day()) + 1;
+    if day <= last_day(year) {
+        (year, day)
+    } else {
+        (year + 1, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn unlocks_at_midnight_est() {
+        let id = PuzzleId::new(2024, 3, 1);
+        assert!(!is_day_unlocked(&id, datetime!(2024-12-03 04:59:59 UTC)));
+        assert!(is_day_unlocked(&id, datetime!(2024-12-03 05:00:00 UTC)));
+    }
+
+    #[test]
+    fn finds_next_unlock() {
+        assert_eq!(next_unlock(datetime!(2025-06-01 12:00 UTC)), (2025, 1));
+        assert_eq!(next_unlock(datetime!(2024-12-03 05:00 UTC)), (2024, 4));
+        assert_eq!(next_unlock(datetime!(2024-12-03 04:59 UTC)), (2024, 3));
+        assert_eq!(next_unlock(datetime!(2025-12-12 05:00 UTC)), (2026, 1));
+        assert_eq!(next_unlock(datetime!(2024-12-31 12:00 UTC)), (2025, 1));
+    }
+
+    #[test]
+    fn simulated_sleep_advances_time() {
+        let clock = SimulatedClock::new(datetime!(2025-12-01 04:59:58 UTC));
+        clock.sleep(Duration::from_secs(2));
+        assert_eq!(clock.now(), unlock_time(2025, 1));
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+/// A private leaderboard, as returned by `/{year}/leaderboard/private/view/{id}.json`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Leaderboard {
+    pub event: String,
+    pub owner_id: u64,
+    pub members: HashMap<String, Member>,
+    /// When the leaderboard was fetched from the server, which may be earlier than now if it was
+    /// read from the cache.
+    #[serde(skip)]
+    pub fetched_at: Option<OffsetDateTime>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u32,
+    #[serde(default)]
+    pub completion_day_level: HashMap<String, HashMap<String, Star>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+
+impl Leaderboard {
+    /// Members ordered by local score, then by stars.
+    #[must_use]
+    pub fn standings(&self) -> Vec<&Member> {
+        let mut members: Vec<_> = self.members.values().collect();
+        members.sort_by(|a, b| {
+            b.local_score
+                .cmp(&a.local_score)
+                .then(b.stars.cmp(&a.stars))
+                .then(a.id.cmp(&b.id))
+        });
+        members
+    }
+}
+
+impl Member {
+    /// The name shown on the site, which is anonymous for members without a public name.
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("(anonymous user #{})", self.id))
+    }
+
+    /// When the member got the star for the given day and part, if they have it.
+    #[must_use]
+    pub fn star(&self, day: u32, part: u32) -> Option<OffsetDateTime> {
+        let star = self
+            .completion_day_level
+            .get(&day.to_string())?
+            .get(&part.to_string())?;
+        OffsetDateTime::from_unix_timestamp(star.get_star_ts).ok()
+    }
+
+    /// Days with at least one star, in order.
+    #[must_use]
+    pub fn days(&self) -> Vec<u32> {
+        let mut days: Vec<u32> = self
+            .completion_day_level
+            .keys()
+            .filter_map(|day| day.parse().ok())
+            .collect();
+        days.sort_unstable();
+        days
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{
+        "event": "2024",
+        "owner_id": 1,
+        "day1_ts": 1733029200,
+        "members": {
+            "1": {
+                "id": 1, "name": "Alice", "stars": 3, "local_score": 10, "global_score": 0,
+                "last_star_ts": 1733116000,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1733029500, "star_index": 10},
+                        "2": {"get_star_ts": 1733029800, "star_index": 20}
+                    },
+                    "2": {"1": {"get_star_ts": 1733116000, "star_index": 30}}
+                }
+            },
+            "2": {
+                "id": 2, "name": null, "stars": 1, "local_score": 4, "global_score": 0,
+                "last_star_ts": 1733030000,
+                "completion_day_level": {"1": {"1": {"get_star_ts": 1733030000, "star_index": 40}}}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parses_leaderboard() {
+        let leaderboard: Leaderboard = serde_json::from_str(JSON).unwrap();
+        let standings = leaderboard.standings();
+
+        assert_eq!(standings[0].display_name(), "Alice");
+        assert_eq!(standings[1].display_name(), "(anonymous user #2)");
+        assert_eq!(standings[0].days(), [1, 2]);
+
+        let part_1 = standings[0].star(1, 1).unwrap();
+        let part_2 = standings[0].star(1, 2).unwrap();
+        assert_eq!((part_2 - part_1).whole_seconds(), 300);
+        assert!(standings[0].star(2, 2).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+use super::SubmitOutcome;
+use serde::{Deserialize, Serialize};
+use std::{fmt, time::Duration};
+use time::OffsetDateTime;
+
+/// A single submitted answer and the verdict it received.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Attempt {
+    pub part: u32,
+    pub answer: String,
+    pub outcome: SubmitOutcome,
+    pub submitted_at: OffsetDateTime,
+}
+
+/// Every answer submitted for a day, used to avoid submissions that are known to be wrong.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    #[serde(default)]
+    pub attempts: Vec<Attempt>,
+}
+
+/// Reasons for refusing to submit an answer without contacting the server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Rejection {
+    AlreadyRejected,
+    AboveUpperBound(i128),
+    BelowLowerBound(i128),
+    LockedOut(Duration),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::AlreadyRejected => write!(f, "answer was already rejected"),
+            Rejection::AboveUpperBound(bound) => write!(f, "answer must be lower than {bound}"),
+            Rejection::BelowLowerBound(bound) => write!(f, "answer must be higher than {bound}"),
+            Rejection::LockedOut(remaining) => write!(
+                f,
+                "answer submitted too recently, {}s left to wait",
+                remaining.as_secs()
+            ),
+        }
+    }
+}
+
+impl Ledger {
+    pub fn record(&mut self, part: u32, answer: &str, outcome: SubmitOutcome) {
+        self.attempts.push(Attempt {
+            part,
+            answer: answer.to_string(),
+            outcome,
+            submitted_at: OffsetDateTime::now_utc(),
+        });
+    }
+
+    pub fn attempts(&self, part: u32) -> impl Iterator<Item = &Attempt> {
+        self.attempts.iter().filter(move |a| a.part == part)
+    }
+
+    /// Checks an answer against earlier verdicts for the same part.
+    pub fn check(&self, part: u32, answer: &str) -> Result<(), Rejection> {
+        if let Some(remaining) = self.remaining_lockout(part) {
+            return Err(Rejection::LockedOut(remaining));
+        }
+
+        let rejected = self.attempts(part).any(|a| {
+            a.answer == answer
+                && matches!(
+                    a.outcome,
+                    SubmitOutcome::TooHigh | SubmitOutcome::TooLow | SubmitOutcome::Wrong
+                )
+        });
+        if rejected {
+            return Err(Rejection::AlreadyRejected);
+        }
+
+        if let Ok(value) = answer.trim().parse::<i128>() {
+            let (lower, upper) = self.bounds(part);
+            if let Some(upper) = upper
+                && value >= upper
+            {
+                return Err(Rejection::AboveUpperBound(upper));
+            }
+            if let Some(lower) = lower
+                && value <= lower
+            {
+                return Err(Rejection::BelowLowerBound(lower));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The largest answer known to be too low and the smallest known to be too high.
+    #[must_use]
+    pub fn bounds(&self, part: u32) -> (Option<i128>, Option<i128>) {
+        let numeric = |outcome: SubmitOutcome| {
+            self.attempts(part)
+                .filter(move |a| a.outcome == outcome)
+                .filter_map(|a| a.answer.trim().parse::<i128>().ok())
+        };
+        let lower = numeric(SubmitOutcome::TooLow).max();
+        let upper = numeric(SubmitOutcome::TooHigh).min();
+        (lower, upper)
+    }
+
+    /// Time left before the server accepts another answer, after a rate-limit verdict.
+    #[must_use]
+    pub fn remaining_lockout(&self, part: u32) -> Option<Duration> {
+        let attempt = self.attempts(part).last()?;
+        let SubmitOutcome::RateLimited(wait) = attempt.outcome else {
+            return None;
+        };
+        let elapsed = OffsetDateTime::now_utc() - attempt.submitted_at;
+        let remaining = wait.checked_sub(elapsed.try_into().unwrap_or_default())?;
+        (!remaining.is_zero()).then_some(remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_previously_wrong_answers() {
+        let mut ledger = Ledger::default();
+        ledger.record(1, "abc", SubmitOutcome::Wrong);
+
+        assert_eq!(ledger.check(1, "abc"), Err(Rejection::AlreadyRejected));
+        assert_eq!(ledger.check(1, "abd"), Ok(()));
+        assert_eq!(ledger.check(2, "abc"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_answers_outside_bounds() {
+        let mut ledger = Ledger::default();
+        ledger.record(1, "100", SubmitOutcome::TooHigh);
+        ledger.record(1, "150", SubmitOutcome::TooHigh);
+        ledger.record(1, "10", SubmitOutcome::TooLow);
+
+        assert_eq!(ledger.bounds(1), (Some(10), Some(100)));
+        assert_eq!(ledger.check(1, "120"), Err(Rejection::AboveUpperBound(100)));
+        assert_eq!(ledger.check(1, "5"), Err(Rejection::BelowLowerBound(10)));
+        assert_eq!(ledger.check(1, "50"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_during_lockout() {
+        let mut ledger = Ledger::default();
+        ledger.record(1, "1", SubmitOutcome::RateLimited(Duration::from_secs(60)));
+
+        assert!(matches!(ledger.check(1, "2"), Err(Rejection::LockedOut(_))));
+
+        ledger.attempts[0].submitted_at -= Duration::from_secs(61);
+        assert_eq!(ledger.check(1, "2"), Ok(()));
+    }
+
+    #[test]
+    fn roundtrips_through_toml() {
+        let mut ledger = Ledger::default();
+        ledger.record(1, "42", SubmitOutcome::TooLow);
+        ledger.record(2, "7", SubmitOutcome::RateLimited(Duration::from_secs(30)));
+
+        let toml = toml::to_string(&ledger).unwrap();
+        let parsed: Ledger = toml::from_str(&toml).unwrap();
+
+        assert_eq!(parsed.attempts.len(), 2);
+        assert_eq!(parsed.attempts[0].outcome, SubmitOutcome::TooLow);
+        assert_eq!(
+            parsed.attempts[1].submitted_at,
+            ledger.attempts[1].submitted_at
+        );
+    }
+}
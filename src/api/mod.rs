@@ -1,57 +1,394 @@
 use crate::PuzzleId;
 use reqwest::{
-    blocking::{Client, Response},
+    Method, StatusCode, Url,
+    blocking::{Client, Request, RequestBuilder, Response},
     header::{self, HeaderMap, HeaderValue, InvalidHeaderValue},
 };
 use scraper::{Html, Selector};
-use time::{Date, Month, OffsetDateTime, Time, UtcOffset};
+use std::{fmt, io, path::PathBuf, sync::Arc, thread, time::Duration};
+use time::OffsetDateTime;
+
+mod assets;
+mod audit;
+mod cache;
+mod calendar;
+mod cassette;
+mod clock;
+mod leaderboard;
+mod ledger;
+mod session;
+mod submit;
+mod throttle;
+
+pub use assets::{resolve_css_urls, resolve_url};
+pub use audit::AuditLog;
+pub use cache::{CachedResponse, HttpCache};
+pub use calendar::{DayStars, EventStars};
+pub use cassette::Cassette;
+pub use clock::{Clock, SimulatedClock, SystemClock, next_unlock, unlock_time};
+pub use leaderboard::{Leaderboard, Member, Star};
+pub use ledger::{Attempt, Ledger, Rejection};
+pub use submit::SubmitOutcome;
+pub use throttle::{FetchLog, Throttle};
 
 const EMAIL: &str = "emil@englesson.net";
 
+/// The Advent of Code website, used unless another base URL is given.
+pub const BASE_URL: &str = "https://adventofcode.com";
+
+/// Directory for local client state, such as the time of the last request and the audit log.
+const STATE_DIR: &str = ".aoc";
+
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(3);
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+/// Advent of Code asks that private leaderboards are fetched at most once every 15 minutes.
+const LEADERBOARD_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub base_url: String,
+    pub state_dir: PathBuf,
+    pub min_interval: Duration,
+    /// Decides whether days have unlocked.
+    pub clock: Arc<dyn Clock>,
+    /// Records requests to, or replays them from, a cassette instead of only using the network.
+    pub cassette: Option<Arc<Cassette>>,
+    /// Encrypts the disk cache of responses. Nothing is cached on disk without it, or with a
+    /// cassette, so that every request is recorded in full and replayed from the cassette alone.
+    pub cache_key: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            base_url: BASE_URL.to_string(),
+            state_dir: PathBuf::from(STATE_DIR),
+            min_interval: MIN_REQUEST_INTERVAL,
+            clock: Arc::new(SystemClock),
+            cassette: None,
+            cache_key: None,
+        }
+    }
+}
+
 pub struct PuzzlePrompt {
     pub description: String,
     pub styles: String,
+    /// The title of the day, from the `--- Day N: Title ---` heading of part 1.
+    pub title: Option<String>,
+    /// The page the puzzle was downloaded from.
+    pub url: String,
 }
 
 pub struct AdventOfCode {
     client: Client,
+    /// Fetches stylesheets, fonts and images without the session cookie.
+    asset_client: Client,
+    base_url: String,
+    throttle: Throttle,
+    audit: AuditLog,
+    cache: Option<HttpCache>,
+    fetch_log: FetchLog,
+    clock: Arc<dyn Clock>,
+    cassette: Option<Arc<Cassette>>,
 }
 
 impl AdventOfCode {
-    pub fn new(session: &str) -> Result<Self, ApiError> {
+    pub fn new(session: &str, options: &Options) -> Result<Self, ApiError> {
         let client = reqwest::blocking::ClientBuilder::new();
 
         let client = client.default_headers(default_headers(session)?);
 
         let client = client.build()?;
-        Ok(Self { client })
+        let asset_client = reqwest::blocking::ClientBuilder::new()
+            .default_headers(asset_headers()?)
+            .build()?;
+        Ok(Self {
+            client,
+            asset_client,
+            base_url: options.base_url.trim_end_matches('/').to_string(),
+            throttle: Throttle::new(
+                &options.state_dir.join("last_request"),
+                options.min_interval,
+            ),
+            audit: AuditLog::new(&options.state_dir.join("requests.log")),
+            cache: options
+                .cache_key
+                .as_ref()
+                .filter(|_| options.cassette.is_none())
+                .map(|key| HttpCache::new(&options.state_dir.join("cache"), key)),
+            fetch_log: FetchLog::new(&options.state_dir.join("fetched")),
+            clock: Arc::clone(&options.clock),
+            cassette: options.cassette.clone(),
+        })
+    }
+
+    /// Whether the puzzle's day has unlocked according to the client's clock.
+    #[must_use]
+    pub fn is_day_unlocked(&self, id: &PuzzleId) -> bool {
+        clock::is_day_unlocked(id, self.clock.now())
+    }
+
+    /// Sends a request once the throttle allows it, retrying server errors with capped
+    /// exponential backoff. Only GET requests are retried, as the server may have acted on a
+    /// POST, such as an answer, before failing.
+    fn send(&self, request: RequestBuilder) -> Result<Response, ApiError> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let max_attempts = if request.method() == Method::GET {
+            MAX_ATTEMPTS
+        } else {
+            1
+        };
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 1;
+
+        loop {
+            let response = self.send_once(&client, clone_request(&request))?;
+            if !response.status().is_server_error() || attempt == max_attempts {
+                return check_status(response);
+            }
+            thread::sleep(backoff);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            attempt += 1;
+        }
+    }
+
+    fn is_replaying(&self) -> bool {
+        self.cassette
+            .as_ref()
+            .is_some_and(|cassette| cassette.is_replaying())
+    }
+
+    fn send_once(&self, client: &Client, request: Request) -> Result<Response, ApiError> {
+        if let Some(cassette) = &self.cassette
+            && cassette.is_replaying()
+        {
+            return cassette.play(&request);
+        }
+
+        self.throttle.wait()?;
+
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+        let result = client.execute(request);
+
+        let status = result
+            .as_ref()
+            .ok()
+            .map(|response| response.status().as_u16());
+        self.audit.record(&method, &url, status)?;
+
+        match &self.cassette {
+            Some(cassette) => cassette.record_response(&method, &url, body, result?),
+            None => Ok(result?),
+        }
     }
 
     fn get_text(&self, url: &str) -> Result<String, ApiError> {
-        let text = self.client.get(url).send().and_then(Response::text)?;
+        let text = self.send(self.client.get(url))?.text()?;
         Ok(text)
     }
 
+    /// Fetches a page through the disk cache. Cached copies younger than `max_age` are used as
+    /// is, older ones are revalidated with conditional requests and used when the server cannot
+    /// be reached. Pages with a `max_age` are not fetched again within it even without a cached
+    /// copy, since the site asks for that.
+    fn get_cached(
+        &self,
+        client: &Client,
+        url: &str,
+        max_age: Duration,
+    ) -> Result<CachedResponse, ApiError> {
+        let cached = self.cache.as_ref().and_then(|cache| cache.get(url));
+        if let Some(cached) = &cached
+            && cached.is_fresh(max_age)
+        {
+            return Ok(cached.clone());
+        }
+        let limited = !max_age.is_zero() && !self.is_replaying();
+        if limited && let Some(remaining) = self.fetch_log.remaining(url, max_age) {
+            return cached.ok_or(ApiError::TooSoon(remaining));
+        }
+
+        let mut request = client.get(url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = match self.send(request) {
+            Ok(response) => response,
+            Err(error @ (ApiError::Reqwest(_) | ApiError::ServerError(_))) => {
+                return cached.ok_or(error);
+            }
+            Err(error) => return Err(error),
+        };
+        if limited {
+            self.fetch_log.record(url)?;
+        }
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(mut cached) = cached
+        {
+            cached.fetched_at = OffsetDateTime::now_utc();
+            self.put_cached(&cached)?;
+            return Ok(cached);
+        }
+
+        let header_value = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        let etag = header_value(header::ETAG);
+        let last_modified = header_value(header::LAST_MODIFIED);
+        let cacheable = response.status().is_success()
+            && (etag.is_some() || last_modified.is_some() || !max_age.is_zero());
+
+        let fetched = CachedResponse {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            fetched_at: OffsetDateTime::now_utc(),
+            body: response.text()?,
+        };
+        if cacheable {
+            self.put_cached(&fetched)?;
+        }
+        Ok(fetched)
+    }
+
+    fn put_cached(&self, response: &CachedResponse) -> Result<(), ApiError> {
+        if let Some(cache) = &self.cache {
+            cache.put(response)?;
+        }
+        Ok(())
+    }
+
+    /// The root of the site the client talks to, without a trailing slash.
+    #[must_use]
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The URL of a day page, which references in stored descriptions are relative to.
+    #[must_use]
+    pub fn day_url(&self, year: u32, day: u32) -> String {
+        format!("{}/{year}/day/{day}", self.base_url)
+    }
+
+    /// Fetches a file such as an image or a font from an absolute URL, with its content type.
+    /// Assets are fetched without the session, and only from the site itself.
+    pub fn get_asset(&self, url: &str) -> Result<(String, Vec<u8>), ApiError> {
+        self.check_asset_host(url)?;
+        let response = self.send(self.asset_client.get(url))?;
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        Ok((content_type, response.bytes()?.to_vec()))
+    }
+
+    fn check_asset_host(&self, url: &str) -> Result<(), ApiError> {
+        let site = Url::parse(&self.base_url).map_err(|_| ApiError::ParseError)?;
+        let url = Url::parse(url).map_err(|_| ApiError::ParseError)?;
+        if url.host_str() != site.host_str()
+            || url.port_or_known_default() != site.port_or_known_default()
+        {
+            return Err(ApiError::ForeignHost(url.to_string()));
+        }
+        Ok(())
+    }
+
+    /// The name of the logged in user, checking that the session is still valid.
+    pub fn whoami(&self) -> Result<String, ApiError> {
+        let html = self.get_text(&format!("{}/settings", self.base_url))?;
+        session::parse_user(&html)
+    }
+
+    pub fn get_events(&self) -> Result<Vec<EventStars>, ApiError> {
+        let html = self.get_text(&format!("{}/events", self.base_url))?;
+        calendar::parse_events(&html)
+    }
+
+    pub fn get_calendar(&self, year: u32) -> Result<Vec<DayStars>, ApiError> {
+        let html = self.get_text(&format!("{}/{year}", self.base_url))?;
+        calendar::parse_calendar(&html, year)
+    }
+
+    /// Collects the star state of every day, visiting only the calendars of events with stars.
+    pub fn get_progress(&self) -> Result<Vec<DayStars>, ApiError> {
+        let mut progress = Vec::new();
+        for event in self.get_events()? {
+            if event.stars > 0 {
+                progress.extend(self.get_calendar(event.year)?);
+            }
+        }
+        progress.sort_by_key(|d| (d.year, d.day));
+        Ok(progress)
+    }
+
+    /// Fetches a private leaderboard, reusing a cached copy for as long as the site asks.
+    pub fn get_leaderboard(&self, year: u32, id: u64) -> Result<Leaderboard, ApiError> {
+        let url = format!(
+            "{}/{year}/leaderboard/private/view/{id}.json",
+            self.base_url
+        );
+        let response = self.get_cached(&self.client, &url, LEADERBOARD_MAX_AGE)?;
+
+        let mut leaderboard: Leaderboard =
+            serde_json::from_str(&response.body).map_err(|_| ApiError::ParseError)?;
+        leaderboard.fetched_at = Some(response.fetched_at);
+        Ok(leaderboard)
+    }
+
     pub fn get_input(&self, id: &PuzzleId) -> Result<String, ApiError> {
-        if !is_day_unlocked(id) {
+        if !self.is_day_unlocked(id) {
             return Err(ApiError::DayLocked);
         }
-        self.get_text(&input_url(id))
+        self.get_text(&self.input_url(id))
     }
 
     pub fn get_puzzle(&self, id: &PuzzleId) -> Result<PuzzlePrompt, ApiError> {
-        if !is_day_unlocked(id) {
+        if !self.is_day_unlocked(id) {
             return Err(ApiError::DayLocked);
         }
-        let html = self.get_text(&to_url(id))?;
-        let puzzles = self.extract_puzzles(&html)?;
+        let url = self.to_url(id);
+        let html = self.get_cached(&self.client, &url, Duration::ZERO)?.body;
+        let puzzles = self.extract_puzzles(&html, &url)?;
         puzzles
             .into_iter()
             .nth(id.part.saturating_sub(1) as usize)
             .ok_or(ApiError::ParseError)
     }
 
-    fn extract_puzzles(&self, html: &str) -> Result<Vec<PuzzlePrompt>, ApiError> {
+    /// Accepted answers of the solved parts of the puzzle's day, in part order.
+    pub fn get_answers(&self, id: &PuzzleId) -> Result<Vec<String>, ApiError> {
+        if !self.is_day_unlocked(id) {
+            return Err(ApiError::DayLocked);
+        }
+        let html = self
+            .get_cached(&self.client, &self.to_url(id), Duration::ZERO)?
+            .body;
+        Ok(submit::parse_answers(&html))
+    }
+
+    fn extract_puzzles(&self, html: &str, url: &str) -> Result<Vec<PuzzlePrompt>, ApiError> {
         let document = Html::parse_document(html);
 
         let article_selector = Selector::parse("article.day-desc").unwrap();
@@ -71,51 +408,110 @@ impl AdventOfCode {
             })
             .filter_map(|link| link.value().attr("href"))
             .filter_map(|href| {
-                let css_url = if href.starts_with("http") {
-                    href.to_string()
-                } else {
-                    format!("https://adventofcode.com{href}")
-                };
-                self.get_text(&css_url).ok()
+                let css_url = resolve_url(url, href)?;
+                self.check_asset_host(&css_url).ok()?;
+                self.get_cached(&self.asset_client, &css_url, Duration::ZERO)
+                    .ok()
+                    .map(|response| resolve_css_urls(&response.body, &css_url))
             })
             .collect::<Vec<_>>()
             .join("\n\n");
 
+        let heading_selector = Selector::parse("h2").unwrap();
+        let title = articles[0]
+            .select(&heading_selector)
+            .next()
+            .and_then(|heading| parse_title(&heading.text().collect::<String>()));
+
         Ok(articles
             .into_iter()
             .map(|article| PuzzlePrompt {
                 description: article.html(),
                 styles: styles.clone(),
+                title: title.clone(),
+                url: url.to_string(),
             })
             .collect())
     }
 
-    // Return an enum instead of a string (Correct, TooHigh, TooLow, RateLimit)?
-    pub fn submit(&self, id: &PuzzleId, solution: String) -> Result<String, ApiError> {
-        if !is_day_unlocked(id) {
+    /// Submits an answer unless the ledger already knows it to be wrong, and records the verdict.
+    pub fn submit(
+        &self,
+        id: &PuzzleId,
+        solution: String,
+        ledger: &mut Ledger,
+    ) -> Result<SubmitOutcome, ApiError> {
+        if !self.is_day_unlocked(id) {
             return Err(ApiError::DayLocked);
         }
-        let params = [("level", id.part.to_string()), ("answer", solution)];
-        let response = self.client.post(answer_url(id)).form(&params).send()?;
+        ledger
+            .check(id.part, &solution)
+            .map_err(ApiError::Rejected)?;
+
+        let params = [("level", id.part.to_string()), ("answer", solution.clone())];
+        let response = self.send(self.client.post(self.answer_url(id)).form(&params))?;
         let text = response.text()?;
-        Ok(text)
+        let outcome = submit::parse_outcome(&text, id.part)?;
+
+        ledger.record(id.part, &solution, outcome.clone());
+        Ok(outcome)
+    }
+
+    #[must_use]
+    fn to_url(&self, id: &PuzzleId) -> String {
+        format!("{}/{}/day/{}", self.base_url, id.year, id.day)
+    }
+
+    #[must_use]
+    fn input_url(&self, id: &PuzzleId) -> String {
+        format!("{}/input", self.to_url(id))
+    }
+
+    #[must_use]
+    fn answer_url(&self, id: &PuzzleId) -> String {
+        format!("{}/answer", self.to_url(id))
     }
 }
 
-fn is_day_unlocked(id: &PuzzleId) -> bool {
-    let est_offset = UtcOffset::from_hms(-5, 0, 0).unwrap();
-    let est_now = OffsetDateTime::now_utc().to_offset(est_offset);
+fn clone_request(request: &Request) -> Request {
+    request
+        .try_clone()
+        .expect("requests are built without streaming bodies")
+}
 
-    let unlock_date = Date::from_calendar_date(
-        id.year.cast_signed(),
-        Month::December,
-        u8::try_from(id.day).unwrap(),
-    )
-    .unwrap()
-    .with_time(Time::MIDNIGHT)
-    .assume_offset(est_offset);
+/// Extracts the title from a heading such as `--- Day 1: Secret Entrance ---`.
+fn parse_title(heading: &str) -> Option<String> {
+    let title = heading
+        .trim()
+        .strip_prefix("--- Day ")?
+        .strip_suffix(" ---")?
+        .split_once(": ")?
+        .1
+        .trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Turns error statuses into errors. Advent of Code answers both unknown and not yet unlocked
+/// pages with 404, and requests that need a session with 400, so the body tells them apart.
+fn check_status(response: Response) -> Result<Response, ApiError> {
+    let status = response.status();
+    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+        return Ok(response);
+    }
+    if status.is_server_error() {
+        return Err(ApiError::ServerError(status.as_u16()));
+    }
 
-    est_now >= unlock_date
+    let body = response.text().unwrap_or_default();
+    Err(match status {
+        StatusCode::NOT_FOUND if body.contains("Please don't repeatedly request this endpoint") => {
+            ApiError::TooEarly
+        }
+        StatusCode::NOT_FOUND => ApiError::NotFound,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ApiError::Unauthorized,
+        StatusCode::BAD_REQUEST if body.contains("log in") => ApiError::Unauthorized,
+        _ => ApiError::Status(status.as_u16()),
+    })
 }
 
 fn default_headers(session: &str) -> Result<HeaderMap, InvalidHeaderValue> {
@@ -135,6 +531,14 @@ fn default_headers(session: &str) -> Result<HeaderMap, InvalidHeaderValue> {
     Ok(headers)
 }
 
+/// Identifies the client like [`default_headers`], without the session.
+fn asset_headers() -> Result<HeaderMap, InvalidHeaderValue> {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::USER_AGENT, HeaderValue::try_from(&user_agent())?);
+    headers.insert(header::FROM, HeaderValue::try_from(EMAIL)?);
+    Ok(headers)
+}
+
 fn user_agent() -> String {
     let repo = env!("CARGO_PKG_REPOSITORY");
     let version = env!("CARGO_PKG_VERSION");
@@ -145,27 +549,71 @@ fn user_agent() -> String {
     format!("{repo}@{version} (contact: {EMAIL})")
 }
 
-#[must_use]
-fn to_url(id: &PuzzleId) -> String {
-    format!("https://adventofcode.com/{}/day/{}", id.year, id.day)
-}
-
-#[must_use]
-fn input_url(id: &PuzzleId) -> String {
-    format!("{}/input", to_url(id))
-}
-
-#[must_use]
-fn answer_url(id: &PuzzleId) -> String {
-    format!("{}/answer", to_url(id))
-}
-
 #[derive(Debug)]
 pub enum ApiError {
     InvalidHeader(header::InvalidHeaderValue),
     Reqwest(reqwest::Error),
+    Io(io::Error),
     ParseError,
     DayLocked,
+    Rejected(Rejection),
+    /// The session is missing, invalid or expired.
+    Unauthorized,
+    NotFound,
+    /// The server asked not to request the page repeatedly before it unlocks.
+    TooEarly,
+    ServerError(u16),
+    /// Any other unsuccessful status.
+    Status(u16),
+    /// The page was fetched recently and may only be fetched again after this long.
+    TooSoon(Duration),
+    Cassette(String),
+    /// An asset is hosted elsewhere, and is not fetched to not reveal the user to other sites.
+    ForeignHost(String),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::InvalidHeader(error) => write!(f, "invalid session header: {error}"),
+            ApiError::Reqwest(error) => write!(f, "request failed: {error}"),
+            ApiError::Io(error) => write!(f, "I/O error: {error}"),
+            ApiError::ParseError => write!(f, "unexpected response from the server"),
+            ApiError::DayLocked => write!(f, "the puzzle has not unlocked yet"),
+            ApiError::Rejected(rejection) => write!(f, "{rejection}"),
+            ApiError::Unauthorized => {
+                write!(
+                    f,
+                    "the session is invalid or has expired, update AOC_SESSION"
+                )
+            }
+            ApiError::NotFound => write!(f, "the page does not exist"),
+            ApiError::TooEarly => write!(
+                f,
+                "the puzzle has not unlocked yet, and the server asks not to request it repeatedly"
+            ),
+            ApiError::ServerError(status) => write!(f, "server error (HTTP {status})"),
+            ApiError::Status(status) => write!(f, "unexpected response (HTTP {status})"),
+            ApiError::TooSoon(remaining) => write!(
+                f,
+                "fetched recently, try again in {} minutes",
+                remaining.as_secs().div_ceil(60)
+            ),
+            ApiError::Cassette(message) => write!(f, "cassette: {message}"),
+            ApiError::ForeignHost(url) => write!(f, "not fetching {url} from another site"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::InvalidHeader(error) => Some(error),
+            ApiError::Reqwest(error) => Some(error),
+            ApiError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
 }
 
 impl From<header::InvalidHeaderValue> for ApiError {
@@ -179,3 +627,9 @@ impl From<reqwest::Error> for ApiError {
         ApiError::Reqwest(error)
     }
 }
+
+impl From<io::Error> for ApiError {
+    fn from(error: io::Error) -> Self {
+        ApiError::Io(error)
+    }
+}
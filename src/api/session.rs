@@ -0,0 +1,45 @@
+use super::ApiError;
+use scraper::{Html, Selector};
+
+/// Extracts the user name from the header of a page, which only shows it when logged in.
+///
+/// The header reads either the user's name or "(anonymous user #N)", followed by the star count.
+pub(super) fn parse_user(html: &str) -> Result<String, ApiError> {
+    let document = Html::parse_document(html);
+    let user_selector = Selector::parse(".user").unwrap();
+
+    let user = document
+        .select(&user_selector)
+        .next()
+        .ok_or(ApiError::Unauthorized)?;
+    let name = user
+        .children()
+        .filter_map(|child| child.value().as_text())
+        .map(|text| text.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_user() {
+        let html =
+            r#"<header><div class="user">Alice <span class="star-count">42*</span></div></header>"#;
+        assert_eq!(parse_user(html).unwrap(), "Alice");
+    }
+
+    #[test]
+    fn logged_out() {
+        let html = r#"<header><div><a href="/auth/login">[Log In]</a></div></header>"#;
+        assert!(matches!(parse_user(html), Err(ApiError::Unauthorized)));
+    }
+}
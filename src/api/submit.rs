@@ -0,0 +1,170 @@
+use super::ApiError;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The verdict returned by Advent of Code after submitting an answer.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmitOutcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    RateLimited(Duration),
+    AlreadyCompleted,
+    WrongLevel,
+}
+
+/// Parses the `<article>` of an answer response.
+///
+/// Advent of Code uses the same message for submitting to an already completed level and for
+/// submitting part 2 before part 1 is solved. Part 1 can only be the wrong level if it has
+/// already been completed, so the submitted part is used to tell the two apart.
+pub(super) fn parse_outcome(html: &str, part: u32) -> Result<SubmitOutcome, ApiError> {
+    let document = Html::parse_document(html);
+    let article_selector = Selector::parse("article").unwrap();
+    let text = document
+        .select(&article_selector)
+        .next()
+        .ok_or(ApiError::ParseError)?
+        .text()
+        .collect::<String>();
+
+    let outcome = if text.contains("That's the right answer") {
+        SubmitOutcome::Correct
+    } else if text.contains("your answer is too high") {
+        SubmitOutcome::TooHigh
+    } else if text.contains("your answer is too low") {
+        SubmitOutcome::TooLow
+    } else if text.contains("That's not the right answer") {
+        SubmitOutcome::Wrong
+    } else if text.contains("You gave an answer too recently") {
+        SubmitOutcome::RateLimited(parse_wait(&text).ok_or(ApiError::ParseError)?)
+    } else if text.contains("You don't seem to be solving the right level") {
+        if part == 1 {
+            SubmitOutcome::AlreadyCompleted
+        } else {
+            SubmitOutcome::WrongLevel
+        }
+    } else {
+        return Err(ApiError::ParseError);
+    };
+
+    Ok(outcome)
+}
+
+/// Extracts the accepted answers of a day page, in part order.
+///
+/// Each solved part is followed by "Your puzzle answer was <code>…</code>".
+pub(super) fn parse_answers(html: &str) -> Vec<String> {
+    let document = Html::parse_document(html);
+    let paragraph_selector = Selector::parse("main p").unwrap();
+    let code_selector = Selector::parse("code").unwrap();
+
+    document
+        .select(&paragraph_selector)
+        .filter(|p| {
+            p.text()
+                .collect::<String>()
+                .starts_with("Your puzzle answer was")
+        })
+        .filter_map(|p| p.select(&code_selector).next())
+        .map(|code| code.text().collect())
+        .collect()
+}
+
+/// Extracts the remaining wait from text such as "You have 1m 5s left to wait".
+fn parse_wait(text: &str) -> Option<Duration> {
+    let sentence = Regex::new(r"You have ([\dhms ]+) left to wait").unwrap();
+    let component = Regex::new(r"(\d+)([hms])").unwrap();
+
+    let remaining = sentence.captures(text)?.get(1)?.as_str();
+    let seconds = component
+        .captures_iter(remaining)
+        .map(|caps| {
+            let value: u64 = caps[1].parse().unwrap();
+            match &caps[2] {
+                "h" => value * 60 * 60,
+                "m" => value * 60,
+                _ => value,
+            }
+        })
+        .sum();
+
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(message: &str) -> String {
+        format!("<html><body><main><article><p>{message}</p></article></main></body></html>")
+    }
+
+    #[test]
+    fn correct() {
+        let html = page("That's the right answer! You are <em>one gold star</em> closer.");
+        assert_eq!(parse_outcome(&html, 1).unwrap(), SubmitOutcome::Correct);
+    }
+
+    #[test]
+    fn too_high_and_too_low() {
+        let high = page("That's not the right answer; your answer is too high.");
+        let low = page("That's not the right answer; your answer is too low.");
+        assert_eq!(parse_outcome(&high, 1).unwrap(), SubmitOutcome::TooHigh);
+        assert_eq!(parse_outcome(&low, 1).unwrap(), SubmitOutcome::TooLow);
+    }
+
+    #[test]
+    fn wrong() {
+        let html = page("That's not the right answer. If you're stuck, make sure...");
+        assert_eq!(parse_outcome(&html, 2).unwrap(), SubmitOutcome::Wrong);
+    }
+
+    #[test]
+    fn rate_limited() {
+        let seconds = page("You gave an answer too recently. You have 37s left to wait.");
+        let minutes = page("You gave an answer too recently. You have 1m 5s left to wait.");
+        assert_eq!(
+            parse_outcome(&seconds, 1).unwrap(),
+            SubmitOutcome::RateLimited(Duration::from_secs(37))
+        );
+        assert_eq!(
+            parse_outcome(&minutes, 1).unwrap(),
+            SubmitOutcome::RateLimited(Duration::from_secs(65))
+        );
+    }
+
+    #[test]
+    fn wrong_level() {
+        let html =
+            page("You don't seem to be solving the right level. Did you already complete it?");
+        assert_eq!(
+            parse_outcome(&html, 1).unwrap(),
+            SubmitOutcome::AlreadyCompleted
+        );
+        assert_eq!(parse_outcome(&html, 2).unwrap(), SubmitOutcome::WrongLevel);
+    }
+
+    #[test]
+    fn unknown_response() {
+        assert!(parse_outcome("<html></html>", 1).is_err());
+        assert!(parse_outcome(&page("Something else entirely"), 1).is_err());
+    }
+
+    #[test]
+    fn answers() {
+        let html = "<html><body><main>\
+            <article class=\"day-desc\"><p>Part one.</p></article>\
+            <p>Your puzzle answer was <code>1234</code>.</p>\
+            <article class=\"day-desc\"><p>Part two, with <code>42</code>.</p></article>\
+            <p>Your puzzle answer was <code>abc</code>.</p>\
+            <p>Both parts of this puzzle are complete!</p>\
+            </main></body></html>";
+        assert_eq!(parse_answers(html), ["1234", "abc"]);
+        assert!(parse_answers(&page("Nothing solved yet")).is_empty());
+    }
+}
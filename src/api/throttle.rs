@@ -0,0 +1,115 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Enforces a minimum interval between requests, across separate invocations of the tool.
+pub struct Throttle {
+    path: PathBuf,
+    min_interval: Duration,
+}
+
+impl Throttle {
+    #[must_use]
+    pub fn new(path: &Path, min_interval: Duration) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            min_interval,
+        }
+    }
+
+    /// Blocks until the minimum interval has passed since the last request, then records a new one.
+    pub fn wait(&self) -> Result<(), io::Error> {
+        if let Some(last) = self.last_request() {
+            let elapsed = SystemTime::now()
+                .duration_since(last)
+                .unwrap_or(Duration::ZERO);
+            if let Some(remaining) = self.min_interval.checked_sub(elapsed) {
+                thread::sleep(remaining);
+            }
+        }
+        self.record()
+    }
+
+    fn last_request(&self) -> Option<SystemTime> {
+        read_time(&self.path)
+    }
+
+    fn record(&self) -> Result<(), io::Error> {
+        write_now(&self.path)
+    }
+}
+
+/// Remembers when pages that may only be fetched every so often were last fetched, by URL. Only
+/// the times are kept, so the interval holds even when the pages themselves cannot be cached.
+pub struct FetchLog {
+    dir: PathBuf,
+}
+
+impl FetchLog {
+    #[must_use]
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// How long until `url` may be fetched again, if it was fetched less than `interval` ago.
+    #[must_use]
+    pub fn remaining(&self, url: &str, interval: Duration) -> Option<Duration> {
+        let elapsed = SystemTime::now()
+            .duration_since(read_time(&self.entry_path(url))?)
+            .unwrap_or(Duration::ZERO);
+        interval.checked_sub(elapsed).filter(|d| !d.is_zero())
+    }
+
+    pub fn record(&self, url: &str) -> Result<(), io::Error> {
+        write_now(&self.entry_path(url))
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(blake3::hash(url.as_bytes()).to_hex().as_str())
+    }
+}
+
+fn read_time(path: &Path) -> Option<SystemTime> {
+    let nanos: u64 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_nanos(nanos))
+}
+
+fn write_now(path: &Path) -> Result<(), io::Error> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_nanos();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, nanos.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    #[test]
+    fn waits_between_requests() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("last_request");
+        let interval = Duration::from_millis(200);
+
+        let start = Instant::now();
+        Throttle::new(&path, interval).wait().unwrap();
+        assert!(start.elapsed() < interval);
+
+        // A fresh throttle picks up the persisted time of the previous request
+        Throttle::new(&path, interval).wait().unwrap();
+        assert!(start.elapsed() >= interval);
+    }
+}
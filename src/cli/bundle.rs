@@ -0,0 +1,109 @@
+use crate::storage::Bundle;
+use clap::{Arg, ArgMatches, Command, value_parser};
+use inquire::Password;
+use std::{env, fs, path::PathBuf};
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("bundle")
+        .about("Move puzzle data between stores in an archive with its own key")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("export")
+                .about("Export puzzle data to an encrypted bundle")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("year")
+                        .long("year")
+                        .help("Only export this year")
+                        .value_parser(value_parser!(u32)),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Import the puzzle data of a bundle, keeping files that already exist")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+}
+
+pub fn execute(matches: &ArgMatches) {
+    let result = match matches.subcommand() {
+        Some(("export", matches)) => export(matches),
+        Some(("import", matches)) => import(matches),
+        _ => unreachable!(),
+    };
+    if let Err(e) = result {
+        println!("Error: {e}");
+    }
+}
+
+fn export(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+    let file = matches.get_one::<PathBuf>("file").unwrap();
+    let year = matches.get_one::<u32>("year").copied();
+
+    let bundle =
+        Bundle::collect(&store, year).map_err(|e| format!("Failed to read puzzle data: {e}"))?;
+    if bundle.is_empty() {
+        return Err("No puzzle data to export".to_string());
+    }
+    let encrypted = bundle
+        .encrypt(&bundle_key(true)?)
+        .map_err(|e| format!("Failed to encrypt bundle: {e}"))?;
+    fs::write(file, encrypted).map_err(|e| format!("Failed to write {}: {e}", file.display()))?;
+
+    println!("Exported {} files to {}", bundle.len(), file.display());
+    Ok(())
+}
+
+fn import(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+    let file = matches.get_one::<PathBuf>("file").unwrap();
+
+    let encrypted =
+        fs::read(file).map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+    let bundle = Bundle::decrypt(&bundle_key(false)?, &encrypted)
+        .map_err(|e| format!("Failed to open bundle: {e}"))?;
+    let merge = bundle
+        .merge_into(&store)
+        .map_err(|e| format!("Failed to import bundle: {e}"))?;
+
+    for path in &merge.conflicts {
+        println!("Conflict: {} differs, kept the stored file", path.display());
+    }
+    println!(
+        "Imported {} files, {} already up to date, {} conflicts",
+        merge.added.len(),
+        merge.unchanged.len(),
+        merge.conflicts.len()
+    );
+    Ok(())
+}
+
+/// The key of a bundle, from `AOC_BUNDLE_KEY` or prompted for, confirming new keys.
+fn bundle_key(confirm: bool) -> Result<String, String> {
+    let key = match env::var("AOC_BUNDLE_KEY") {
+        Ok(key) => key,
+        Err(_) => {
+            let prompt = Password::new("Bundle key:");
+            let prompt = if confirm {
+                prompt
+            } else {
+                prompt.without_confirmation()
+            };
+            prompt.prompt().map_err(|_| "Selection cancelled")?
+        }
+    };
+    if key.is_empty() {
+        return Err("The bundle key must not be empty".to_string());
+    }
+    Ok(key)
+}
@@ -1,6 +1,39 @@
-use crate::{PuzzleId, api::AdventOfCode, storage};
-use clap::{Arg, ArgMatches, Command, value_parser};
+use crate::{
+    PuzzleId,
+    api::AdventOfCode,
+    storage::{Error, PuzzleMetadata, PuzzleStore},
+};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use std::env;
+use time::OffsetDateTime;
+
+/// What is missing locally for a puzzle part.
+struct Missing {
+    prompt: bool,
+    input: bool,
+    answers: bool,
+}
+
+impl Missing {
+    /// Files that cannot be read are reported rather than downloaded again over.
+    fn check(id: &PuzzleId, solved: u32, store: &dyn PuzzleStore) -> Result<Self, String> {
+        let read_error = |e: Error| {
+            format!(
+                "Failed to read stored puzzle {} day {:02} part {}: {e}",
+                id.year, id.day, id.part
+            )
+        };
+        Ok(Self {
+            prompt: check_needs_prompt(id, store).map_err(read_error)?,
+            input: check_needs_input(id, store).map_err(read_error)?,
+            answers: check_needs_answers(id, solved, store).map_err(read_error)?,
+        })
+    }
+
+    fn any(&self) -> bool {
+        self.prompt || self.input || self.answers
+    }
+}
 
 #[must_use]
 pub fn command() -> Command {
@@ -8,21 +41,23 @@ pub fn command() -> Command {
         .about("Download puzzle description and styles")
         .arg(
             Arg::new("year")
-                .value_parser(value_parser!(u32))
-                .required(true)
+                .value_parser(super::year_parser())
+                .required_unless_present("all")
                 .index(1),
         )
         .arg(
             Arg::new("day")
-                .value_parser(value_parser!(u32))
-                .required(true)
+                .value_parser(super::day_parser())
+                .requires("part")
                 .index(2),
         )
+        .arg(Arg::new("part").value_parser(super::part_parser()).index(3))
         .arg(
-            Arg::new("part")
-                .value_parser(value_parser!(u32))
-                .required(true)
-                .index(3),
+            Arg::new("all")
+                .long("all")
+                .help("Download every unlocked puzzle of every event")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("year"),
         )
 }
 
@@ -33,88 +68,310 @@ pub fn execute(matches: &ArgMatches) {
 }
 
 fn try_execute(matches: &ArgMatches) -> Result<(), String> {
-    let password = env::var("AOC_KEY").map_err(|_| "AOC_KEY environment variable not set")?;
+    let store = super::puzzle_store(matches)?;
     let session =
         env::var("AOC_SESSION").map_err(|_| "AOC_SESSION environment variable not set")?;
 
-    let year = matches.get_one::<u32>("year").copied().unwrap();
-    let day = matches.get_one::<u32>("day").copied().unwrap();
-    let part = matches.get_one::<u32>("part").copied().unwrap();
+    let year = matches.get_one::<u32>("year").copied();
+    let day = matches.get_one::<u32>("day").copied();
+    let part = matches.get_one::<u32>("part").copied();
 
-    let id = PuzzleId::new(year, day, part);
+    match (year, day, part) {
+        (Some(year), Some(day), Some(part)) => {
+            download_one(matches, &PuzzleId::new(year, day, part), &session, &store)
+        }
+        (Some(year), _, _) => download_many(matches, Some(year), &session, &store),
+        _ => download_many(matches, None, &session, &store),
+    }
+}
 
-    let needs_prompt = check_needs_prompt(&id, &password);
-    let needs_input = check_needs_input(&id, &password);
+fn download_one(
+    matches: &ArgMatches,
+    id: &PuzzleId,
+    session: &str,
+    store: &dyn PuzzleStore,
+) -> Result<(), String> {
+    let missing = Missing::check(id, known_solved(id, store), store)?;
 
-    if !needs_prompt && !needs_input {
-        println!("Puzzle {year} day {day:02} part {part} already downloaded");
+    if !missing.any() {
+        println!(
+            "Puzzle {} day {:02} part {} already downloaded",
+            id.year, id.day, id.part
+        );
         return Ok(());
     }
 
-    let api = AdventOfCode::new(&session).map_err(|_| "Failed to initialize API client")?;
+    let api = super::authenticated_client(matches, session)?;
+    download(&api, id, &missing, store)
+}
 
-    let downloaded_prompt = if needs_prompt {
-        download_prompt(&api, &id, &password)?;
-        true
-    } else {
-        false
+/// Downloads whatever is missing of a puzzle part with an existing client.
+pub(super) fn download_missing(
+    api: &AdventOfCode,
+    id: &PuzzleId,
+    store: &dyn PuzzleStore,
+) -> Result<(), String> {
+    download(
+        api,
+        id,
+        &Missing::check(id, known_solved(id, store), store)?,
+        store,
+    )
+}
+
+/// How many parts of the day are known to be solved without the calendar. Both answers are looked
+/// for until the puzzle page has been searched once, after that the recorded stars are trusted.
+/// Asking for a part means the parts before it are solved, even if they were not when recorded.
+fn known_solved(id: &PuzzleId, store: &dyn PuzzleStore) -> u32 {
+    match store.read_metadata(id) {
+        Ok(metadata) if metadata.answers_checked_at.is_some() => metadata.stars.max(id.part - 1),
+        _ => 2,
+    }
+}
+
+/// Downloads every unlocked part of a year, or of all events, skipping what is already cached.
+fn download_many(
+    matches: &ArgMatches,
+    year: Option<u32>,
+    session: &str,
+    store: &dyn PuzzleStore,
+) -> Result<(), String> {
+    let api = super::authenticated_client(matches, session)?;
+
+    let years = match year {
+        Some(year) => vec![year],
+        None => {
+            let mut years: Vec<_> = api
+                .get_events()
+                .map_err(|e| format!("Failed to fetch events: {e}"))?
+                .into_iter()
+                .map(|event| event.year)
+                .collect();
+            years.sort_unstable();
+            years
+        }
     };
 
-    let downloaded_input = if needs_input {
-        download_input(&api, &id, &password)?;
-        true
+    let (mut downloaded, mut cached, mut failed) = (0, 0, 0);
+    for year in years {
+        let calendar = api
+            .get_calendar(year)
+            .map_err(|e| format!("Failed to fetch calendar of {year}: {e}"))?;
+
+        for day in calendar {
+            // Part 2 is only available once part 1 is solved
+            for part in 1..=day.stars.clamp(1, 2) {
+                let id = PuzzleId::new(year, day.day, part);
+                if !api.is_day_unlocked(&id) {
+                    continue;
+                }
+
+                let missing = match Missing::check(&id, day.stars, store) {
+                    Ok(missing) if !missing.any() => {
+                        cached += 1;
+                        continue;
+                    }
+                    Ok(missing) => missing,
+                    Err(e) => {
+                        println!("Error: {e}");
+                        failed += 1;
+                        continue;
+                    }
+                };
+
+                match download(&api, &id, &missing, store) {
+                    Ok(()) => downloaded += 1,
+                    Err(e) => {
+                        println!("Error: {e}");
+                        failed += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    println!("Downloaded {downloaded} parts, {cached} already cached, {failed} failed");
+    Ok(())
+}
+
+fn download(
+    api: &AdventOfCode,
+    id: &PuzzleId,
+    missing: &Missing,
+    store: &dyn PuzzleStore,
+) -> Result<(), String> {
+    let PuzzleId { year, day, part } = id;
+
+    if missing.prompt {
+        download_prompt(api, id, store)?;
+    }
+
+    if missing.input {
+        download_input(api, id, store)?;
+    }
+
+    let recovered = if missing.answers {
+        recover_answers(api, id, store)?
     } else {
-        false
+        Vec::new()
     };
 
-    match (downloaded_prompt, downloaded_input) {
+    match (missing.prompt, missing.input) {
         (true, true) => println!("Downloaded puzzle and input for {year} day {day:02} part {part}"),
         (true, false) => println!("Downloaded puzzle for {year} day {day:02} part {part}"),
         (false, true) => println!("Downloaded input for {year} day {day:02} part {part}"),
-        (false, false) => unreachable!(),
+        (false, false) if recovered.is_empty() => {
+            println!("Puzzle {year} day {day:02} part {part} already downloaded");
+        }
+        (false, false) => {}
+    }
+    for part in recovered {
+        println!("Recovered answer for {year} day {day:02} part {part}");
     }
     Ok(())
 }
 
-fn check_needs_prompt(id: &PuzzleId, password: &str) -> bool {
-    storage::get_description(id, password)
-        .map(|desc| desc.description.is_empty())
-        .unwrap_or(true)
+fn check_needs_prompt(id: &PuzzleId, store: &dyn PuzzleStore) -> Result<bool, Error> {
+    match store.get_description(id) {
+        Ok(desc) => Ok(desc.description.is_empty()),
+        Err(e) if e.is_not_found() => Ok(true),
+        Err(e) => Err(e),
+    }
 }
 
-fn check_needs_input(id: &PuzzleId, password: &str) -> bool {
-    storage::read_input(id, password).is_empty()
+fn check_needs_input(id: &PuzzleId, store: &dyn PuzzleStore) -> Result<bool, Error> {
+    match store.read_input_file(id, 1) {
+        Ok(_) => Ok(false),
+        Err(e) if e.is_not_found() => Ok(true),
+        Err(e) => Err(e),
+    }
 }
 
-fn download_prompt(api: &AdventOfCode, id: &PuzzleId, password: &str) -> Result<(), String> {
+/// Whether any of the first `solved` parts of the day is missing the expected output of its input.
+fn check_needs_answers(id: &PuzzleId, solved: u32, store: &dyn PuzzleStore) -> Result<bool, Error> {
+    for part in 1..=solved.min(2) {
+        if store
+            .read_input(&PuzzleId::new(id.year, id.day, part))?
+            .is_empty()
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn download_prompt(
+    api: &AdventOfCode,
+    id: &PuzzleId,
+    store: &dyn PuzzleStore,
+) -> Result<(), String> {
     let prompt = api.get_puzzle(id).map_err(|e| {
         format!(
-            "Failed to download puzzle {} day {:02} part {}: {e:?}",
+            "Failed to download puzzle {} day {:02} part {}: {e}",
             id.year, id.day, id.part
         )
     })?;
 
-    storage::save_description(
-        id,
-        Some(&prompt.description),
-        Some(&prompt.styles),
-        password,
-    )
-    .map_err(|_| "Failed to save puzzle description")?;
+    store
+        .save_description(id, Some(&prompt.description), Some(&prompt.styles))
+        .map_err(|_| "Failed to save puzzle description")?;
+
+    // Metadata is shared by both parts of the day
+    let mut metadata = read_metadata(id, store)?;
+    if let Some(title) = prompt.title {
+        metadata.title = title;
+    }
+    metadata.downloaded_at = Some(OffsetDateTime::now_utc());
+    metadata.source = Some(prompt.url);
+    store
+        .save_metadata(id, &metadata)
+        .map_err(|_| "Failed to save metadata")?;
 
     Ok(())
 }
 
-fn download_input(api: &AdventOfCode, id: &PuzzleId, password: &str) -> Result<(), String> {
+/// The metadata of a day, or the defaults if none is stored yet. Metadata that cannot be read is
+/// an error, so that it is not saved over.
+fn read_metadata(id: &PuzzleId, store: &dyn PuzzleStore) -> Result<PuzzleMetadata, String> {
+    match store.read_metadata(id) {
+        Ok(metadata) => Ok(metadata),
+        Err(e) if e.is_not_found() => Ok(PuzzleMetadata::default()),
+        Err(e) => Err(format!("Failed to read metadata: {e}")),
+    }
+}
+
+fn download_input(
+    api: &AdventOfCode,
+    id: &PuzzleId,
+    store: &dyn PuzzleStore,
+) -> Result<(), String> {
     let input = api.get_input(id).map_err(|e| {
         format!(
-            "Failed to download input for {} day {:02} part {}: {e:?}",
+            "Failed to download input for {} day {:02} part {}: {e}",
             id.year, id.day, id.part
         )
     })?;
 
-    storage::save_input(id, 1, &input, None, password)
+    store
+        .save_input(id, 1, &input, None)
         .map_err(|_| "Failed to save puzzle input")?;
 
     Ok(())
 }
+
+/// Saves the answers accepted by Advent of Code as expected outputs of the day's input, for the
+/// parts that do not have one yet. Returns the parts that were filled in.
+fn recover_answers(
+    api: &AdventOfCode,
+    id: &PuzzleId,
+    store: &dyn PuzzleStore,
+) -> Result<Vec<u32>, String> {
+    let answers = api.get_answers(id).map_err(|e| {
+        format!(
+            "Failed to download answers for {} day {:02}: {e}",
+            id.year, id.day
+        )
+    })?;
+
+    // Both parts share the same input
+    let mut input = None;
+    for part in 1..=2 {
+        match store.read_input_file(&PuzzleId::new(id.year, id.day, part), 1) {
+            Ok(contents) => {
+                input = Some(contents);
+                break;
+            }
+            Err(e) if e.is_not_found() => {}
+            Err(e) => return Err(format!("Failed to read puzzle input: {e}")),
+        }
+    }
+    let Some(input) = input else {
+        return Ok(Vec::new());
+    };
+
+    // Remember the search, so the page is only fetched again once more stars are seen
+    let mut metadata = read_metadata(id, store)?;
+    metadata.stars = metadata
+        .stars
+        .max(u32::try_from(answers.len()).unwrap_or(2));
+    metadata.answers_checked_at = Some(OffsetDateTime::now_utc());
+    store
+        .save_metadata(id, &metadata)
+        .map_err(|_| "Failed to save metadata")?;
+
+    let mut recovered = Vec::new();
+    for (part, answer) in (1..).zip(answers) {
+        let part_id = PuzzleId::new(id.year, id.day, part);
+        let inputs = store
+            .read_input(&part_id)
+            .map_err(|_| "Failed to read puzzle inputs")?;
+        if inputs.is_empty() {
+            store
+                .save_input(&part_id, 1, &input, Some(&answer))
+                .map_err(|_| "Failed to save puzzle answer")?;
+            recovered.push(part);
+        }
+    }
+
+    Ok(recovered)
+}
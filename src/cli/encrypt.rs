@@ -1,14 +1,22 @@
-use crate::storage;
+use crate::storage::{self, PlaintextStore};
 use clap::{ArgMatches, Command};
-use std::env;
 
 #[must_use]
 pub fn command() -> Command {
     Command::new("encrypt")
 }
 
-pub fn execute(_matches: &ArgMatches) {
-    let password = env::var("AOC_KEY").unwrap();
-    storage::encrypt_all(&password).unwrap();
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let data_dir = super::data_dir(matches)?;
+    let store = super::puzzle_store(matches)?;
+    storage::copy_all(&PlaintextStore::open(&data_dir), &store)
+        .map_err(|_| "Failed to encrypt files")?;
     println!("All files encrypted successfully!");
+    Ok(())
 }
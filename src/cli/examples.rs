@@ -0,0 +1,203 @@
+use crate::{PuzzleId, storage::PuzzleStore};
+use clap::{Arg, ArgMatches, Command};
+use inquire::{Confirm, Text};
+use scraper::{ElementRef, Html};
+
+/// An example input from a puzzle description, with the highlighted values that follow it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Example {
+    input: String,
+    answers: Vec<String>,
+}
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("examples")
+        .about("Extract example inputs from a stored description into test cases")
+        .arg(
+            Arg::new("year")
+                .value_parser(super::year_parser())
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new("day")
+                .value_parser(super::day_parser())
+                .required(true)
+                .index(2),
+        )
+        .arg(
+            Arg::new("part")
+                .value_parser(super::part_parser())
+                .required(true)
+                .index(3),
+        )
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+
+    let year = matches.get_one::<u32>("year").copied().unwrap();
+    let day = matches.get_one::<u32>("day").copied().unwrap();
+    let part = matches.get_one::<u32>("part").copied().unwrap();
+
+    let id = PuzzleId::new(year, day, part);
+    let description = store
+        .get_description(&id)
+        .map_err(|_| format!("No description stored for {year} day {day:02} part {part}"))?;
+
+    let mut examples = find_examples(&description.description);
+    // Part 2 usually refers back to the examples of part 1 with new answers
+    if examples.is_empty() && part == 2 {
+        let first = store
+            .get_description(&PuzzleId::new(year, day, 1))
+            .map_err(|_| format!("No description stored for {year} day {day:02} part 1"))?;
+        let answers = highlighted_values(&description.description);
+        examples = find_examples(&first.description)
+            .into_iter()
+            .map(|example| Example {
+                input: example.input,
+                answers: answers.clone(),
+            })
+            .collect();
+    }
+
+    if examples.is_empty() {
+        return Err("No examples found in the description".to_string());
+    }
+
+    let mut next_id = store
+        .read_tests(&id)
+        .map_err(|_| "Failed to read test cases")?
+        .iter()
+        .map(|test| test.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    for (index, example) in examples.iter().enumerate() {
+        println!("=== Example {} ===", index + 1);
+        println!("{}", example.input.trim_end());
+        if !example.answers.is_empty() {
+            println!("Highlighted: {}", example.answers.join(", "));
+        }
+
+        let accepted = Confirm::new("Save as test case?")
+            .with_default(false)
+            .prompt()
+            .map_err(|_| "Selection cancelled")?;
+        if !accepted {
+            continue;
+        }
+
+        let guess = example.answers.last().cloned().unwrap_or_default();
+        let expected = Text::new("Expected output:")
+            .with_default(&guess)
+            .prompt()
+            .map_err(|_| "Selection cancelled")?;
+
+        store
+            .save_test(&id, next_id, &example.input, &expected)
+            .map_err(|_| "Failed to save test case")?;
+        println!("Saved test {next_id:02}");
+        next_id += 1;
+    }
+
+    Ok(())
+}
+
+/// Finds `<pre><code>` blocks, each paired with the highlighted values that follow it.
+fn find_examples(html: &str) -> Vec<Example> {
+    let fragment = Html::parse_fragment(html);
+    let mut examples: Vec<Example> = Vec::new();
+
+    for element in fragment
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+    {
+        if element.value().name() == "pre" {
+            examples.push(Example {
+                input: element.text().collect(),
+                answers: Vec::new(),
+            });
+        } else if let Some(value) = highlighted_value(element)
+            && let Some(example) = examples.last_mut()
+        {
+            example.answers.push(value);
+        }
+    }
+
+    examples
+}
+
+fn highlighted_values(html: &str) -> Vec<String> {
+    let fragment = Html::parse_fragment(html);
+    fragment
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter_map(highlighted_value)
+        .collect()
+}
+
+/// The text of `<code><em>` (or `<em><code>`) outside of example blocks, which is how answers
+/// are highlighted in descriptions.
+fn highlighted_value(element: ElementRef) -> Option<String> {
+    let outer = match element.value().name() {
+        "em" => "code",
+        "code" => "em",
+        _ => return None,
+    };
+    let parent = element.parent().and_then(ElementRef::wrap)?;
+    if parent.value().name() != outer || parent.children().count() != 1 {
+        return None;
+    }
+    let in_example = element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| ancestor.value().name() == "pre");
+    (!in_example).then(|| element.text().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_examples_with_following_answers() {
+        let html = "<article>\
+            <p>For example:</p><pre><code>1\n<em>2</em>\n</code></pre>\
+            <p>This gives <code>4</code>, then <code><em>7</em></code>.</p>\
+            <p>Another:</p><pre><code>3\n</code></pre>\
+            <p>Which gives <em><code>9</code></em>.</p>\
+            </article>";
+
+        assert_eq!(
+            find_examples(html),
+            [
+                Example {
+                    input: "1\n2\n".to_string(),
+                    answers: vec!["7".to_string()],
+                },
+                Example {
+                    input: "3\n".to_string(),
+                    answers: vec!["9".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_answers_without_examples() {
+        let html = "<article><p>Now it is <code><em>12</em></code>, not <em>13</em>.</p></article>";
+        assert!(find_examples(html).is_empty());
+        assert_eq!(highlighted_values(html), ["12"]);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::api::{Leaderboard, Member};
+use clap::{Arg, ArgMatches, Command, value_parser};
+use std::env;
+use time::{
+    Month, OffsetDateTime, UtcOffset, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
+
+const TIMESTAMP_FORMAT: &[BorrowedFormatItem<'_>] =
+    format_description!("[month repr:short] [day] [hour]:[minute]:[second]");
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("leaderboard")
+        .about("Show the standings of a private leaderboard")
+        .arg(
+            Arg::new("id")
+                .value_parser(value_parser!(u64))
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new("year")
+                .long("year")
+                .value_parser(value_parser!(u32)),
+        )
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let session =
+        env::var("AOC_SESSION").map_err(|_| "AOC_SESSION environment variable not set")?;
+
+    let id = matches.get_one::<u64>("id").copied().unwrap();
+    let year = matches
+        .get_one::<u32>("year")
+        .copied()
+        .unwrap_or_else(latest_event);
+
+    let api = super::api_client(matches, &session)?;
+    let leaderboard = api
+        .get_leaderboard(year, id)
+        .map_err(|e| format!("Failed to fetch leaderboard {id} for {year}: {e}"))?;
+
+    print_leaderboard(&leaderboard, id);
+    Ok(())
+}
+
+/// The most recent event, which starts in December.
+fn latest_event() -> u32 {
+    let now = OffsetDateTime::now_utc();
+    let year = now.year().cast_unsigned();
+    if now.month() == Month::December {
+        year
+    } else {
+        year - 1
+    }
+}
+
+fn print_leaderboard(leaderboard: &Leaderboard, id: u64) {
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let fetched = leaderboard
+        .fetched_at
+        .map_or("unknown".to_string(), |time| format_time(time, offset));
+
+    println!(
+        "Private leaderboard {id} ({}), fetched {fetched}",
+        leaderboard.event
+    );
+    println!();
+    println!("  #  Score  Stars  Name");
+    let standings = leaderboard.standings();
+    for (rank, member) in standings.iter().enumerate() {
+        println!(
+            "{:>3}  {:>5}  {:>5}  {}",
+            rank + 1,
+            member.local_score,
+            member.stars,
+            member.display_name()
+        );
+    }
+
+    for member in standings.iter().filter(|member| member.stars > 0) {
+        println!();
+        print_member(member, offset);
+    }
+}
+
+fn print_member(member: &Member, offset: UtcOffset) {
+    println!("{}", member.display_name());
+    println!("  Day  Part 1           Part 2           Gap");
+    for day in member.days() {
+        let part_1 = member.star(day, 1);
+        let part_2 = member.star(day, 2);
+        let format = |time: Option<OffsetDateTime>| {
+            time.map_or("-".to_string(), |time| format_time(time, offset))
+        };
+        let gap = match (part_1, part_2) {
+            (Some(part_1), Some(part_2)) => format_gap(part_2 - part_1),
+            _ => "-".to_string(),
+        };
+        println!(
+            "  {day:>3}  {:<15}  {:<15}  {gap}",
+            format(part_1),
+            format(part_2)
+        );
+    }
+}
+
+fn format_time(time: OffsetDateTime, offset: UtcOffset) -> String {
+    time.to_offset(offset).format(TIMESTAMP_FORMAT).unwrap()
+}
+
+fn format_gap(gap: time::Duration) -> String {
+    let seconds = gap.whole_seconds();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
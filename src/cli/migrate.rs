@@ -0,0 +1,35 @@
+use crate::storage::Kdf;
+use clap::{Arg, ArgMatches, Command, builder::PossibleValuesParser};
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("migrate")
+        .about("Re-encrypt all puzzle data in the current file format")
+        .arg(
+            Arg::new("kdf")
+                .long("kdf")
+                .help("How keys are derived from AOC_KEY, defaults to the current one")
+                .value_parser(PossibleValuesParser::new(["blake3", "argon2id"])),
+        )
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+    let kdf = matches
+        .get_one::<String>("kdf")
+        .and_then(|name| Kdf::from_name(name))
+        .unwrap_or(store.kdf());
+
+    let count = store
+        .migrate(kdf)
+        .map_err(|e| format!("Failed to migrate, no files were changed: {e}"))?;
+
+    println!("Migrated {count} files to {}", kdf.name());
+    Ok(())
+}
@@ -1,19 +1,55 @@
-use clap::{Command, crate_name, crate_version};
+use crate::{
+    api::{AdventOfCode, ApiError, Cassette, Clock, Options, SimulatedClock, SystemClock},
+    storage::{self, EncryptedStore},
+};
+use clap::{
+    Arg, ArgMatches, Command, builder::RangedI64ValueParser, crate_name, crate_version,
+    value_parser,
+};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
 
+pub mod bundle;
 pub mod decrypt;
 pub mod download;
 pub mod encrypt;
+pub mod examples;
+pub mod leaderboard;
+pub mod migrate;
+pub mod open;
+pub mod rekey;
+pub mod show;
 pub mod solve;
+pub mod submit;
+pub mod sync;
+pub mod wait;
+pub mod whoami;
 
 pub fn run() {
     let mut app = build_cli();
     let matches = app.clone().get_matches();
 
     match matches.subcommand() {
+        Some(("bundle", matches)) => bundle::execute(matches),
         Some(("decrypt", matches)) => decrypt::execute(matches),
         Some(("download", matches)) => download::execute(matches),
         Some(("encrypt", matches)) => encrypt::execute(matches),
+        Some(("examples", matches)) => examples::execute(matches),
+        Some(("leaderboard", matches)) => leaderboard::execute(matches),
+        Some(("migrate", matches)) => migrate::execute(matches),
+        Some(("open", matches)) => open::execute(matches),
+        Some(("rekey", matches)) => rekey::execute(matches),
+        Some(("show", matches)) => show::execute(matches),
         Some(("solve", matches)) => solve::execute(matches),
+        Some(("submit", matches)) => submit::execute(matches),
+        Some(("sync", matches)) => sync::execute(matches),
+        Some(("wait", matches)) => wait::execute(matches),
+        Some(("whoami", matches)) => whoami::execute(matches),
         None => println!("No subcommand was used"),
         _ => app.print_help().unwrap(),
     }
@@ -27,8 +63,146 @@ fn build_cli() -> Command {
         .subcommand_required(true)
         .propagate_version(true)
         .arg_required_else_help(true)
+        .arg(
+            Arg::new("data-dir")
+                .long("data-dir")
+                .help("Directory containing puzzles.enc, found from the working directory if unset")
+                .value_parser(value_parser!(PathBuf))
+                .global(true),
+        )
+        .subcommand(bundle::command())
         .subcommand(decrypt::command())
         .subcommand(download::command())
         .subcommand(encrypt::command())
+        .subcommand(examples::command())
+        .subcommand(leaderboard::command())
+        .subcommand(migrate::command())
+        .subcommand(open::command())
+        .subcommand(rekey::command())
+        .subcommand(show::command())
         .subcommand(solve::command())
+        .subcommand(submit::command())
+        .subcommand(sync::command())
+        .subcommand(wait::command())
+        .subcommand(whoami::command())
+}
+
+/// Parses the year of an event, which Advent of Code has held since 2015.
+fn year_parser() -> RangedI64ValueParser<u32> {
+    value_parser!(u32).range(2015..=9999)
+}
+
+/// Parses a day of an event, so that unlock times are only computed for real days.
+fn day_parser() -> RangedI64ValueParser<u32> {
+    value_parser!(u32).range(1..=25)
+}
+
+/// Parses a part of a day, of which there are two.
+fn part_parser() -> RangedI64ValueParser<u32> {
+    value_parser!(u32).range(1..=2)
+}
+
+/// The directory containing `puzzles.enc`, from `--data-dir`, `AOC_DATA_DIR`, or the nearest
+/// directory at or above the working directory that has one.
+fn data_dir(matches: &ArgMatches) -> Result<PathBuf, String> {
+    if let Some(dir) = matches.get_one::<PathBuf>("data-dir") {
+        return Ok(dir.clone());
+    }
+    if let Some(dir) = env::var_os("AOC_DATA_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    let current = env::current_dir().map_err(|_| "Failed to read the working directory")?;
+    storage::find_data_dir(&current).ok_or_else(|| {
+        "No puzzles.enc found in the working directory or its parents, \
+         pass --data-dir or set AOC_DATA_DIR"
+            .to_string()
+    })
+}
+
+/// The encrypted puzzle store in the data directory, unlocked with `AOC_KEY`.
+fn puzzle_store(matches: &ArgMatches) -> Result<EncryptedStore, String> {
+    let password = env::var("AOC_KEY").map_err(|_| "AOC_KEY environment variable not set")?;
+    EncryptedStore::unlock(&data_dir(matches)?, &password).map_err(|e| e.to_string())
+}
+
+/// The real clock, or a simulated one starting at `AOC_NOW` (RFC 3339) if it is set.
+fn clock() -> Result<Arc<dyn Clock>, String> {
+    match env::var("AOC_NOW") {
+        Ok(now) => {
+            let now = OffsetDateTime::parse(&now, &Rfc3339)
+                .map_err(|_| "AOC_NOW must be an RFC 3339 timestamp")?;
+            Ok(Arc::new(SimulatedClock::new(now)))
+        }
+        Err(_) => Ok(Arc::new(SystemClock)),
+    }
+}
+
+/// Creates an API client, talking to `AOC_BASE_URL` instead of Advent of Code if it is set.
+/// `AOC_MIN_INTERVAL_MS` overrides the minimum interval between requests. `AOC_RECORD` records
+/// every request to an encrypted cassette, and `AOC_REPLAY` answers requests from one instead.
+/// Responses are cached on disk, encrypted with `AOC_KEY`, if it is set.
+/// The request state is kept in the data directory if there is one, and in the working directory
+/// otherwise.
+fn api_client(matches: &ArgMatches, session: &str) -> Result<AdventOfCode, String> {
+    api_client_with_clock(matches, session, clock()?)
+}
+
+fn api_client_with_clock(
+    matches: &ArgMatches,
+    session: &str,
+    clock: Arc<dyn Clock>,
+) -> Result<AdventOfCode, String> {
+    let mut options = Options {
+        clock,
+        ..Options::default()
+    };
+    if let Ok(root) = data_dir(matches) {
+        options.state_dir = root.join(&options.state_dir);
+    }
+    if let Ok(base_url) = env::var("AOC_BASE_URL") {
+        options.base_url = base_url;
+    }
+    if let Ok(interval) = env::var("AOC_MIN_INTERVAL_MS") {
+        let millis = interval
+            .parse()
+            .map_err(|_| "AOC_MIN_INTERVAL_MS must be a number of milliseconds")?;
+        options.min_interval = Duration::from_millis(millis);
+    }
+    options.cassette = cassette()?.map(Arc::new);
+    options.cache_key = env::var("AOC_KEY").ok();
+    AdventOfCode::new(session, &options).map_err(|_| "Failed to initialize API client".into())
+}
+
+fn cassette() -> Result<Option<Cassette>, String> {
+    let (record, replay) = (env::var_os("AOC_RECORD"), env::var_os("AOC_REPLAY"));
+    if record.is_none() && replay.is_none() {
+        return Ok(None);
+    }
+    let password = env::var("AOC_KEY").map_err(|_| "AOC_KEY environment variable not set")?;
+
+    match (record, replay) {
+        (Some(_), Some(_)) => Err("AOC_RECORD and AOC_REPLAY cannot both be set".to_string()),
+        (Some(path), None) => Ok(Some(Cassette::record(Path::new(&path), &password))),
+        (None, Some(path)) => Cassette::replay(Path::new(&path), &password)
+            .map(Some)
+            .map_err(|e| format!("Failed to load cassette: {e}")),
+        (None, None) => unreachable!(),
+    }
+}
+
+/// Creates an API client and checks that the session is valid before it is used.
+fn authenticated_client(matches: &ArgMatches, session: &str) -> Result<AdventOfCode, String> {
+    let api = api_client(matches, session)?;
+    check_session(&api)?;
+    Ok(api)
+}
+
+/// Returns the name of the logged in user.
+fn check_session(api: &AdventOfCode) -> Result<String, String> {
+    api.whoami().map_err(|e| match e {
+        ApiError::Unauthorized => {
+            "AOC_SESSION is invalid or has expired, log in again and update it".to_string()
+        }
+        e => format!("Failed to check session: {e}"),
+    })
 }
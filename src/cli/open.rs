@@ -0,0 +1,333 @@
+use crate::{
+    PuzzleId,
+    api::{resolve_css_urls, resolve_url},
+    storage::PuzzleStore,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use regex::{Captures, Regex};
+use std::{env, io::Write, process};
+use tempfile::Builder;
+
+/// A stored part of a puzzle, with its accepted answer if known.
+struct Part {
+    description: String,
+    styles: String,
+    answer: Option<String>,
+}
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("open")
+        .about("Export both parts of a puzzle to a self-contained HTML file")
+        .arg(
+            Arg::new("year")
+                .value_parser(super::year_parser())
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new("day")
+                .value_parser(super::day_parser())
+                .required(true)
+                .index(2),
+        )
+        .arg(
+            Arg::new("open")
+                .long("open")
+                .help("Open the file in the default browser instead of printing its path")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+
+    let year = matches.get_one::<u32>("year").copied().unwrap();
+    let day = matches.get_one::<u32>("day").copied().unwrap();
+
+    let mut parts = Vec::new();
+    for part in 1..=2 {
+        let id = PuzzleId::new(year, day, part);
+        let description = match store.get_description(&id) {
+            Ok(description) => description,
+            Err(e) if e.is_not_found() => continue,
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read the description of part {part}: {e}"
+                ));
+            }
+        };
+        let answer = store
+            .read_input(&id)
+            .map_err(|e| format!("Failed to read the answer of part {part}: {e}"))?
+            .into_iter()
+            .next()
+            .map(|input| input.expected);
+        parts.push(Part {
+            description: description.description,
+            styles: description.styles,
+            answer,
+        });
+    }
+    if parts.is_empty() {
+        return Err(format!("No description stored for {year} day {day:02}"));
+    }
+
+    // Static assets do not need a session
+    let session = env::var("AOC_SESSION").unwrap_or_default();
+    let api = super::api_client(matches, &session)?;
+    let mut missing = 0;
+    let page_url = api.day_url(year, day);
+    let page = build_page(
+        year,
+        day,
+        &parts,
+        api.base_url(),
+        &page_url,
+        &mut |url| match api.get_asset(url) {
+            Ok(asset) => Some(asset),
+            Err(_) => {
+                missing += 1;
+                None
+            }
+        },
+    );
+    if missing > 0 {
+        println!("Could not inline {missing} assets");
+    }
+
+    // A new file with a random name that only the user can read, kept for the browser
+    let (mut file, path) = Builder::new()
+        .prefix(&format!("aoc_{year}_{day:02}_"))
+        .suffix(".html")
+        .tempfile()
+        .and_then(|file| file.keep().map_err(|e| e.error))
+        .map_err(|_| "Failed to create HTML file")?;
+    file.write_all(page.as_bytes())
+        .map_err(|_| "Failed to write HTML file")?;
+
+    if matches.get_flag("open") {
+        open(&path.to_string_lossy())?;
+    } else {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+fn open(path: &str) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        process::Command::new("open")
+    } else {
+        process::Command::new("xdg-open")
+    };
+    command
+        .arg(path)
+        .status()
+        .map_err(|_| format!("Failed to open {path}"))?;
+    Ok(())
+}
+
+/// Reconstructs the day page around the stored descriptions, so that the stored styles apply.
+/// Links point at `base_url`, and references to assets are resolved against `page_url`, the page
+/// the descriptions are from.
+fn build_page(
+    year: u32,
+    day: u32,
+    parts: &[Part],
+    base_url: &str,
+    page_url: &str,
+    fetch: &mut impl FnMut(&str) -> Option<(String, Vec<u8>)>,
+) -> String {
+    let mut styles: Vec<&str> = Vec::new();
+    for part in parts {
+        if !styles.contains(&part.styles.as_str()) {
+            styles.push(&part.styles);
+        }
+    }
+    let styles = inline_css(&styles.join("\n\n"), page_url, fetch);
+
+    let mut main = String::new();
+    for part in parts {
+        main.push_str(&inline_images(
+            &absolute_links(&part.description, base_url),
+            page_url,
+            fetch,
+        ));
+        main.push('\n');
+        if let Some(answer) = &part.answer {
+            main.push_str(&format!(
+                "<p>Your puzzle answer was <code>{}</code>.</p>\n",
+                escape(answer)
+            ));
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en-us\">\n<head>\n<meta charset=\"utf-8\"/>\n\
+         <title>Day {day} - Advent of Code {year}</title>\n<style>\n{styles}\n</style>\n</head>\n\
+         <body>\n<header><div><h1 class=\"title-global\"><a href=\"{base_url}/\">Advent of Code</a>\
+         </h1></div><div><h1 class=\"title-event\">&nbsp;&nbsp;<span class=\"title-event-wrap\">\
+         {{year=</span><a href=\"{base_url}/{year}\">{year}</a><span class=\"title-event-wrap\">}}\
+         </span></h1></div></header>\n\
+         <main>\n{main}</main>\n</body>\n</html>\n"
+    )
+}
+
+/// Replaces `@import` rules with the imported stylesheets and `url(...)` references with data
+/// URLs, leaving references that cannot be fetched as they are. References in an imported
+/// stylesheet are relative to that stylesheet, and all others to `base`.
+fn inline_css(
+    css: &str,
+    base: &str,
+    fetch: &mut impl FnMut(&str) -> Option<(String, Vec<u8>)>,
+) -> String {
+    let import =
+        Regex::new(r#"@import\s+(?:url\(\s*)?['"]?([^'")\s;]+)['"]?\s*\)?[^;]*;"#).unwrap();
+    let url = Regex::new(r#"url\(\s*['"]?([^'")]+)['"]?\s*\)"#).unwrap();
+
+    let css = import.replace_all(css, |caps: &Captures| {
+        resolve_url(base, &caps[1])
+            .and_then(|url| {
+                let (_, bytes) = fetch(&url)?;
+                Some(resolve_css_urls(&String::from_utf8(bytes).ok()?, &url))
+            })
+            .unwrap_or_else(|| caps[0].to_string())
+    });
+    url.replace_all(&css, |caps: &Captures| {
+        if caps[1].starts_with("data:") {
+            return caps[0].to_string();
+        }
+        resolve_url(base, &caps[1])
+            .and_then(|url| fetch(&url))
+            .map_or_else(
+                || caps[0].to_string(),
+                |asset| format!("url(\"{}\")", data_url(&asset)),
+            )
+    })
+    .into_owned()
+}
+
+fn inline_images(
+    html: &str,
+    base: &str,
+    fetch: &mut impl FnMut(&str) -> Option<(String, Vec<u8>)>,
+) -> String {
+    let image = Regex::new(r#"(<img\b[^>]*\bsrc=")([^"]+)(")"#).unwrap();
+    image
+        .replace_all(html, |caps: &Captures| {
+            if caps[2].starts_with("data:") {
+                return caps[0].to_string();
+            }
+            resolve_url(base, &caps[2])
+                .and_then(|url| fetch(&url))
+                .map_or_else(
+                    || caps[0].to_string(),
+                    |asset| format!("{}{}{}", &caps[1], data_url(&asset), &caps[3]),
+                )
+        })
+        .into_owned()
+}
+
+/// Points root-relative links at the site, since the file is opened from disk.
+fn absolute_links(html: &str, base_url: &str) -> String {
+    let link = Regex::new(r#"href="/([^/])"#).unwrap();
+    link.replace_all(html, format!("href=\"{base_url}/$1"))
+        .into_owned()
+}
+
+fn data_url((content_type, bytes): &(String, Vec<u8>)) -> String {
+    format!("data:{content_type};base64,{}", STANDARD.encode(bytes))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "https://adventofcode.com/2024/day/1";
+
+    fn fetch(url: &str) -> Option<(String, Vec<u8>)> {
+        match url {
+            "https://adventofcode.com/static/fonts/font.css" => Some((
+                "text/css".to_string(),
+                b"@font-face { src: url(font.woff2); }".to_vec(),
+            )),
+            "https://adventofcode.com/static/fonts/font.woff2" => {
+                Some(("font/woff2".to_string(), vec![1, 2, 3]))
+            }
+            "https://adventofcode.com/static/image.png" => {
+                Some(("image/png".to_string(), vec![4, 5]))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn inlines_assets() {
+        // The font is relative to the imported stylesheet, not to the page
+        let css = "@import url(/static/fonts/font.css);\nbody { background: url('missing.png'); }";
+        let css = inline_css(css, PAGE, &mut fetch);
+        assert_eq!(
+            css,
+            "@font-face { src: url(\"data:font/woff2;base64,AQID\"); }\n\
+             body { background: url('missing.png'); }"
+        );
+
+        let html = "<p><img alt=\"x\" src=\"/static/image.png\"/></p>";
+        assert_eq!(
+            inline_images(html, PAGE, &mut fetch),
+            "<p><img alt=\"x\" src=\"data:image/png;base64,BAU=\"/></p>"
+        );
+    }
+
+    #[test]
+    fn builds_page() {
+        let parts = [
+            Part {
+                description: "<article class=\"day-desc\"><h2>--- Day 1: Test ---</h2>\
+                    <p>See <a href=\"/2024/about\">about</a>.</p></article>"
+                    .to_string(),
+                styles: "main { color: #ccc; }".to_string(),
+                answer: Some("42".to_string()),
+            },
+            Part {
+                description: "<article class=\"day-desc\"><h2>--- Part Two ---</h2></article>"
+                    .to_string(),
+                styles: "main { color: #ccc; }".to_string(),
+                answer: None,
+            },
+        ];
+        let page = build_page(
+            2024,
+            1,
+            &parts,
+            "http://localhost:8080",
+            "http://localhost:8080/2024/day/1",
+            &mut fetch,
+        );
+
+        assert_eq!(page.matches("main { color: #ccc; }").count(), 1);
+        assert!(page.contains("<title>Day 1 - Advent of Code 2024</title>"));
+        // Links follow the site the page was exported from
+        assert!(page.contains("href=\"http://localhost:8080/2024/about\""));
+        assert!(page.contains("<a href=\"http://localhost:8080/2024\">2024</a>"));
+        assert!(page.contains("Your puzzle answer was <code>42</code>."));
+        assert!(page.contains("--- Part Two ---"));
+    }
+}
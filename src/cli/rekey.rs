@@ -0,0 +1,34 @@
+use clap::{ArgMatches, Command};
+use inquire::Password;
+use std::env;
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("rekey").about("Re-encrypt all puzzle data with a new key")
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+    let new_password = match env::var("AOC_NEW_KEY") {
+        Ok(password) => password,
+        Err(_) => Password::new("New key:")
+            .prompt()
+            .map_err(|_| "Selection cancelled")?,
+    };
+    if new_password.is_empty() {
+        return Err("The new key must not be empty".to_string());
+    }
+
+    let count = store
+        .rekey(&new_password)
+        .map_err(|e| format!("Failed to re-encrypt, no files were changed: {e}"))?;
+
+    println!("Re-encrypted {count} files, set AOC_KEY to the new key");
+    Ok(())
+}
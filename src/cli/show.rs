@@ -0,0 +1,331 @@
+use crate::{PuzzleId, storage::PuzzleStore};
+use clap::{Arg, ArgMatches, Command};
+use crossterm::style::{Color, ContentStyle, Stylize};
+use scraper::{ElementRef, Html, node::Node};
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    process::{self, Stdio},
+};
+
+const DEFAULT_WIDTH: usize = 80;
+const DEFAULT_PAGER: &str = "less -R";
+
+/// How a piece of text is highlighted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Style {
+    /// AoC's `<em>` highlighting, used for answers and important words.
+    em: bool,
+    code: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Span {
+    text: String,
+    style: Style,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Block {
+    Heading(String),
+    Paragraph(Vec<Span>),
+    Code(Vec<Span>),
+    Item(Vec<Span>),
+}
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("show")
+        .about("Show a stored puzzle description in the terminal")
+        .arg(
+            Arg::new("year")
+                .value_parser(super::year_parser())
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new("day")
+                .value_parser(super::day_parser())
+                .required(true)
+                .index(2),
+        )
+        .arg(Arg::new("part").value_parser(super::part_parser()).index(3))
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+
+    let year = matches.get_one::<u32>("year").copied().unwrap();
+    let day = matches.get_one::<u32>("day").copied().unwrap();
+    let parts = match matches.get_one::<u32>("part").copied() {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+
+    let mut descriptions = Vec::new();
+    for part in parts {
+        match store.get_description(&PuzzleId::new(year, day, part)) {
+            Ok(description) => descriptions.push(description.description),
+            Err(e) if e.is_not_found() => {}
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read the description of part {part}: {e}"
+                ));
+            }
+        }
+    }
+    if descriptions.is_empty() {
+        return Err(format!("No description stored for {year} day {day:02}"));
+    }
+
+    let terminal = io::stdout().is_terminal();
+    let (columns, rows) = crossterm::terminal::size()
+        .map_or((DEFAULT_WIDTH, usize::MAX), |(columns, rows)| {
+            (usize::from(columns), usize::from(rows))
+        });
+    let width = if terminal { columns } else { DEFAULT_WIDTH };
+
+    let lines: Vec<_> = descriptions
+        .iter()
+        .flat_map(|html| render(html, width, terminal))
+        .collect();
+
+    if terminal && lines.len() >= rows && page(&lines).is_ok() {
+        return Ok(());
+    }
+    for line in lines {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Shows the lines in `$PAGER`, or `less`.
+fn page(lines: &[String]) -> io::Result<()> {
+    let pager = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
+
+    let mut child = process::Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may be closed before everything is written
+        let _ = writeln!(stdin, "{}", lines.join("\n"));
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Renders a description as lines wrapped to the width, with ANSI styles if `styled`.
+fn render(html: &str, width: usize, styled: bool) -> Vec<String> {
+    let fragment = Html::parse_fragment(html);
+    let mut blocks = Vec::new();
+    collect_blocks(fragment.root_element(), &mut blocks);
+
+    let width = width.max(20);
+    let mut lines = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Heading(text) => {
+                let heading = ContentStyle::new().bold().with(Color::White);
+                lines.push(paint(&text, heading, styled));
+            }
+            Block::Paragraph(spans) => lines.extend(wrap(&spans, width, "", "", styled)),
+            Block::Item(spans) => lines.extend(wrap(&spans, width, "  • ", "    ", styled)),
+            Block::Code(spans) => lines.extend(code_lines(&spans, styled)),
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+fn collect_blocks(element: ElementRef, blocks: &mut Vec<Block>) {
+    for child in element.children() {
+        if let Some(element) = ElementRef::wrap(child) {
+            match element.value().name() {
+                "h2" | "h3" => blocks.push(Block::Heading(element.text().collect())),
+                "p" => blocks.push(Block::Paragraph(spans(element))),
+                "pre" => blocks.push(Block::Code(spans(element))),
+                "li" => blocks.push(Block::Item(spans(element))),
+                _ => collect_blocks(element, blocks),
+            }
+        } else if let Node::Text(text) = child.value()
+            && !text.trim().is_empty()
+        {
+            blocks.push(Block::Paragraph(vec![Span {
+                text: text.to_string(),
+                style: Style::default(),
+            }]));
+        }
+    }
+}
+
+fn spans(element: ElementRef) -> Vec<Span> {
+    let mut spans = Vec::new();
+    collect_spans(element, Style::default(), &mut spans);
+    spans
+}
+
+fn collect_spans(element: ElementRef, style: Style, spans: &mut Vec<Span>) {
+    for child in element.children() {
+        if let Some(element) = ElementRef::wrap(child) {
+            let style = match element.value().name() {
+                "em" => Style { em: true, ..style },
+                "code" => Style {
+                    code: true,
+                    ..style
+                },
+                _ => style,
+            };
+            collect_spans(element, style, spans);
+        } else if let Node::Text(text) = child.value() {
+            spans.push(Span {
+                text: text.to_string(),
+                style,
+            });
+        }
+    }
+}
+
+/// Word wraps spans, keeping punctuation attached to highlighted words.
+fn wrap(spans: &[Span], width: usize, first: &str, rest: &str, styled: bool) -> Vec<String> {
+    let mut words: Vec<Vec<Span>> = Vec::new();
+    let mut current: Vec<Span> = Vec::new();
+    for span in spans {
+        for (index, piece) in span.text.split(char::is_whitespace).enumerate() {
+            if index > 0 && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            if !piece.is_empty() {
+                current.push(Span {
+                    text: piece.to_string(),
+                    style: span.style,
+                });
+            }
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    let mut lines = Vec::new();
+    let mut line = first.to_string();
+    let mut length = first.chars().count();
+    let mut empty = true;
+    for word in words {
+        let word_length: usize = word.iter().map(|span| span.text.chars().count()).sum();
+        if !empty && length + 1 + word_length > width {
+            lines.push(std::mem::replace(&mut line, rest.to_string()));
+            length = rest.chars().count();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+            length += 1;
+        }
+        for span in &word {
+            line.push_str(&paint(&span.text, span_style(span.style), styled));
+        }
+        length += word_length;
+        empty = false;
+    }
+    if !empty {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Code blocks keep their layout and are indented instead of wrapped.
+fn code_lines(spans: &[Span], styled: bool) -> Vec<String> {
+    let mut lines = vec![String::from("  ")];
+    for span in spans {
+        let style = span_style(Style {
+            code: true,
+            ..span.style
+        });
+        for (index, piece) in span.text.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(String::from("  "));
+            }
+            if !piece.is_empty() {
+                lines
+                    .last_mut()
+                    .unwrap()
+                    .push_str(&paint(piece, style, styled));
+            }
+        }
+    }
+    if lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+fn span_style(style: Style) -> ContentStyle {
+    let mut content = ContentStyle::new();
+    if style.code {
+        content = content.with(Color::Grey).on(Color::Black);
+    }
+    if style.em {
+        content = content.bold().with(Color::White);
+    }
+    content
+}
+
+fn paint(text: &str, style: ContentStyle, styled: bool) -> String {
+    if styled {
+        style.apply(text).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = "<article class=\"day-desc\"><h2>--- Day 1: Secret Entrance ---</h2>\
+        <p>The dial starts by pointing at <code>50</code>, and <em>every</em> rotation counts.</p>\
+        <pre><code>L68\nL30\n</code></pre>\
+        <ul><li>One item</li><li>Another item that is long enough to wrap around</li></ul>\
+        <p>The answer is <code><em>3</em></code>.</p></article>";
+
+    #[test]
+    fn renders_blocks() {
+        assert_eq!(
+            render(HTML, 40, false),
+            [
+                "--- Day 1: Secret Entrance ---",
+                "",
+                "The dial starts by pointing at 50, and",
+                "every rotation counts.",
+                "",
+                "  L68",
+                "  L30",
+                "",
+                "  • One item",
+                "",
+                "  • Another item that is long enough to",
+                "    wrap around",
+                "",
+                "The answer is 3.",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn styles_highlights() {
+        let lines = render(HTML, 40, true);
+        let highlighted = ContentStyle::new().bold().with(Color::White).apply("every");
+        assert!(lines[3].starts_with(&highlighted.to_string()));
+        assert!(lines[0].contains("\u{1b}["));
+    }
+}
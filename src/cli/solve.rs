@@ -1,7 +1,10 @@
-use crate::{PuzzleId, solve, storage};
-use clap::{Arg, ArgMatches, Command, value_parser};
+use crate::{
+    PuzzleId, solve,
+    storage::{self, PuzzleStore},
+};
+use clap::{Arg, ArgMatches, Command};
 use inquire::Select;
-use std::{env, time::Instant};
+use std::time::Instant;
 
 enum InputType<'a> {
     Test(&'a storage::TestCase),
@@ -11,9 +14,9 @@ enum InputType<'a> {
 #[must_use]
 pub fn command() -> Command {
     Command::new("solve")
-        .arg(Arg::new("year").value_parser(value_parser!(u32)).index(1))
-        .arg(Arg::new("day").value_parser(value_parser!(u32)).index(2))
-        .arg(Arg::new("part").value_parser(value_parser!(u32)).index(3))
+        .arg(Arg::new("year").value_parser(super::year_parser()).index(1))
+        .arg(Arg::new("day").value_parser(super::day_parser()).index(2))
+        .arg(Arg::new("part").value_parser(super::part_parser()).index(3))
 }
 
 pub fn execute(matches: &ArgMatches) {
@@ -23,7 +26,7 @@ pub fn execute(matches: &ArgMatches) {
 }
 
 fn try_execute(matches: &ArgMatches) -> Result<(), String> {
-    let password = env::var("AOC_KEY").map_err(|_| "AOC_KEY environment variable not set")?;
+    let store = super::puzzle_store(matches)?;
 
     let year = matches.get_one::<u32>("year").copied();
     let day = matches.get_one::<u32>("day").copied();
@@ -32,13 +35,14 @@ fn try_execute(matches: &ArgMatches) -> Result<(), String> {
     // If all arguments provided, run directly
     if let (Some(y), Some(d), Some(p)) = (year, day, part) {
         let puzzle_id = PuzzleId::new(y, d, p);
-        let puzzle = storage::get(&puzzle_id, &password)
+        let puzzle = store
+            .get(&puzzle_id)
             .map_err(|_| format!("Failed to load puzzle {y} day {d:02} part {p}"))?;
         return run_puzzle(&puzzle);
     }
 
     // Load and filter puzzles
-    let puzzles = storage::get_all(&password).map_err(|_| "Failed to load puzzles")?;
+    let puzzles = store.get_all().map_err(|_| "Failed to load puzzles")?;
     let filtered: Vec<_> = puzzles
         .iter()
         .filter(|p| year.is_none_or(|y| p.id.year == y))
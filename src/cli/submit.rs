@@ -0,0 +1,112 @@
+use crate::{
+    PuzzleId,
+    api::{ApiError, SubmitOutcome},
+    solve,
+    storage::PuzzleStore,
+};
+use clap::{Arg, ArgMatches, Command};
+use std::env;
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("submit")
+        .about("Submit an answer, solving the stored input if no answer is given")
+        .arg(
+            Arg::new("year")
+                .value_parser(super::year_parser())
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new("day")
+                .value_parser(super::day_parser())
+                .required(true)
+                .index(2),
+        )
+        .arg(
+            Arg::new("part")
+                .value_parser(super::part_parser())
+                .required(true)
+                .index(3),
+        )
+        .arg(Arg::new("answer").index(4))
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+    let session =
+        env::var("AOC_SESSION").map_err(|_| "AOC_SESSION environment variable not set")?;
+
+    let year = matches.get_one::<u32>("year").copied().unwrap();
+    let day = matches.get_one::<u32>("day").copied().unwrap();
+    let part = matches.get_one::<u32>("part").copied().unwrap();
+
+    let id = PuzzleId::new(year, day, part);
+
+    let input = store.read_input_file(&id, 1).ok();
+    let answer = match matches.get_one::<String>("answer") {
+        Some(answer) => answer.clone(),
+        None => {
+            let input = input
+                .as_deref()
+                .ok_or("No stored input found, download it first")?;
+            solve(year, day, part, input).ok_or(format!(
+                "Solution not implemented for year {year}, day {day:02}, part {part}"
+            ))?
+        }
+    };
+
+    println!("Submitting {answer} for {year} day {day:02} part {part}");
+
+    let mut ledger = store
+        .read_ledger(&id)
+        .map_err(|_| "Failed to read answer ledger")?;
+
+    // Check the ledger before the session, to not contact the server for a known rejection
+    ledger
+        .check(part, &answer)
+        .map_err(|rejection| rejection.to_string())?;
+
+    let api = super::authenticated_client(matches, &session)?;
+    let outcome = match api.submit(&id, answer.clone(), &mut ledger) {
+        Ok(outcome) => outcome,
+        Err(ApiError::Rejected(rejection)) => {
+            return Err(rejection.to_string());
+        }
+        Err(e) => return Err(format!("Failed to submit answer: {e}")),
+    };
+
+    store
+        .save_ledger(&id, &ledger)
+        .map_err(|_| "Failed to save answer ledger")?;
+
+    match outcome {
+        SubmitOutcome::Correct => {
+            println!("✓ That's the right answer!");
+            if let Some(input) = input {
+                store
+                    .save_input(&id, 1, &input, Some(&answer))
+                    .map_err(|_| "Failed to save puzzle answer")?;
+            }
+        }
+        SubmitOutcome::TooHigh => println!("✗ Wrong answer, too high"),
+        SubmitOutcome::TooLow => println!("✗ Wrong answer, too low"),
+        SubmitOutcome::Wrong => println!("✗ Wrong answer"),
+        SubmitOutcome::RateLimited(wait) => {
+            println!(
+                "Answer submitted too recently, {}s left to wait",
+                wait.as_secs()
+            );
+        }
+        SubmitOutcome::AlreadyCompleted => println!("Puzzle already completed"),
+        SubmitOutcome::WrongLevel => println!("Wrong level, is part 1 solved?"),
+    }
+
+    Ok(())
+}
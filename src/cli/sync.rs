@@ -0,0 +1,79 @@
+use crate::storage::PuzzleStore;
+use clap::{ArgMatches, Command};
+use std::{collections::BTreeMap, env};
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("sync").about("Record the stars of the account and compare them to local data")
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+    let session =
+        env::var("AOC_SESSION").map_err(|_| "AOC_SESSION environment variable not set")?;
+
+    let api = super::api_client(matches, &session)?;
+    let progress = api
+        .get_progress()
+        .map_err(|e| format!("Failed to fetch progress: {e}"))?;
+
+    let stars: BTreeMap<_, _> = progress
+        .iter()
+        .filter(|d| d.stars > 0)
+        .map(|d| ((d.year, d.day), d.stars))
+        .collect();
+    // Metadata is stored per day, so any part identifies it
+    let local: BTreeMap<_, _> = store
+        .list()
+        .into_iter()
+        .map(|id| ((id.year, id.day), id))
+        .collect();
+
+    let mut updated = 0;
+    for ((year, day), id) in &local {
+        let Ok(mut metadata) = store.read_metadata(id) else {
+            continue;
+        };
+        let day_stars = stars.get(&(*year, *day)).copied().unwrap_or(0);
+        if metadata.stars != day_stars {
+            metadata.stars = day_stars;
+            store
+                .save_metadata(id, &metadata)
+                .map_err(|_| format!("Failed to save metadata for {year} day {day:02}"))?;
+            updated += 1;
+        }
+    }
+    println!("Updated stars of {updated} days");
+
+    let missing: Vec<_> = stars
+        .iter()
+        .filter(|(key, _)| !local.contains_key(key))
+        .collect();
+    if !missing.is_empty() {
+        println!();
+        println!("Stars without local data:");
+        for ((year, day), count) in missing {
+            println!("  {year} day {day:02} ({count} stars)");
+        }
+    }
+
+    let unsolved: Vec<_> = local
+        .keys()
+        .filter(|key| !stars.contains_key(key))
+        .collect();
+    if !unsolved.is_empty() {
+        println!();
+        println!("Local data without stars:");
+        for (year, day) in unsolved {
+            println!("  {year} day {day:02}");
+        }
+    }
+
+    Ok(())
+}
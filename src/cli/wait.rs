@@ -0,0 +1,128 @@
+use crate::{PuzzleId, api, solve, storage::PuzzleStore};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::{
+    env,
+    io::{self, Write},
+    sync::Arc,
+    time::Duration,
+};
+
+/// How long to wait after the unlock before downloading, to not hit the server the instant the
+/// puzzle becomes available.
+const UNLOCK_DELAY: Duration = Duration::from_secs(5);
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("wait")
+        .about("Count down to a puzzle unlock, then download it")
+        .arg(
+            Arg::new("year")
+                .value_parser(super::year_parser())
+                .requires("day")
+                .index(1),
+        )
+        .arg(Arg::new("day").value_parser(super::day_parser()).index(2))
+        .arg(
+            Arg::new("test")
+                .long("test")
+                .help("Run the solver on the stored tests and input once downloaded")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let store = super::puzzle_store(matches)?;
+    let session =
+        env::var("AOC_SESSION").map_err(|_| "AOC_SESSION environment variable not set")?;
+
+    let clock = super::clock()?;
+    let (year, day) = match (
+        matches.get_one::<u32>("year").copied(),
+        matches.get_one::<u32>("day").copied(),
+    ) {
+        (Some(year), Some(day)) => (year, day),
+        _ => api::next_unlock(clock.now()),
+    };
+    let id = PuzzleId::new(year, day, 1);
+
+    // Check the session now rather than finding out at midnight
+    let api = super::api_client_with_clock(matches, &session, Arc::clone(&clock))?;
+    super::check_session(&api)?;
+
+    let unlock = api::unlock_time(year, day);
+    if clock.now() < unlock {
+        println!("Waiting for {year} day {day:02}");
+        loop {
+            let remaining = unlock - clock.now();
+            if !remaining.is_positive() {
+                break;
+            }
+            print!("\rUnlocks in {}", format_remaining(remaining));
+            io::stdout().flush().map_err(|e| e.to_string())?;
+
+            let step = remaining
+                .min(time::Duration::SECOND)
+                .try_into()
+                .unwrap_or(Duration::ZERO);
+            clock.sleep(step);
+        }
+        println!();
+        println!("Unlocked, downloading in {}s", UNLOCK_DELAY.as_secs());
+        clock.sleep(UNLOCK_DELAY);
+    }
+
+    super::download::download_missing(&api, &id, &store)?;
+
+    if matches.get_flag("test") {
+        run_solver(&id, &store)?;
+    }
+    Ok(())
+}
+
+fn run_solver(id: &PuzzleId, store: &dyn PuzzleStore) -> Result<(), String> {
+    let PuzzleId { year, day, part } = id;
+    let run = |input: &str| solve(*year, *day, *part, input);
+
+    let tests = store
+        .read_tests(id)
+        .map_err(|_| "Failed to read test cases")?;
+    for test in tests {
+        match run(&test.input) {
+            Some(result) if result == test.expected => println!("Test {:02}: ✓ PASS", test.id),
+            Some(result) => println!(
+                "Test {:02}: ✗ FAIL (got {result}, expected {})",
+                test.id, test.expected
+            ),
+            None => {
+                println!("Solution not implemented for year {year}, day {day:02}, part {part}");
+                return Ok(());
+            }
+        }
+    }
+
+    if let Ok(input) = store.read_input_file(id, 1) {
+        match run(&input) {
+            Some(result) => println!("Answer: {result}"),
+            None => {
+                println!("Solution not implemented for year {year}, day {day:02}, part {part}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn format_remaining(remaining: time::Duration) -> String {
+    let seconds = remaining.whole_seconds();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,24 @@
+use clap::{ArgMatches, Command};
+use std::env;
+
+#[must_use]
+pub fn command() -> Command {
+    Command::new("whoami").about("Check the session and show the logged in user")
+}
+
+pub fn execute(matches: &ArgMatches) {
+    if let Err(e) = try_execute(matches) {
+        println!("Error: {e}");
+    }
+}
+
+fn try_execute(matches: &ArgMatches) -> Result<(), String> {
+    let session =
+        env::var("AOC_SESSION").map_err(|_| "AOC_SESSION environment variable not set")?;
+
+    let api = super::api_client(matches, &session)?;
+    let user = super::check_session(&api)?;
+
+    println!("Logged in as {user}");
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use super::{
+    cipher::{Kdf, SaltedKey},
+    components::{
+        Error,
+        ledger::LEDGER_FILE,
+        puzzle::{STYLES_DIR, stylesheet_path},
+    },
+    store::PuzzleStore,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+};
+
+/// The version of the archive format written by this version of the code.
+const BUNDLE_VERSION: u32 = 1;
+
+/// Authenticated with every bundle, so that other encrypted files are not mistaken for one.
+const ASSOCIATED_DATA: &[u8] = b"aoc bundle";
+
+/// The contents of a bundle once decrypted. Every file is listed in the manifest by its blake3
+/// hash, which is checked before anything is imported.
+#[derive(Debug, Serialize, Deserialize)]
+struct Archive {
+    version: u32,
+    manifest: BTreeMap<String, String>,
+    /// File contents, base64 encoded.
+    files: BTreeMap<String, String>,
+}
+
+/// A selection of puzzle data that can be moved between stores under its own password.
+///
+/// Bundles hold metadata, descriptions, styles, tests and inputs. Answer ledgers are personal and
+/// are left out.
+#[derive(Debug, Default)]
+pub struct Bundle {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+/// The outcome of importing a bundle, by path relative to the root of the store.
+#[derive(Debug, Default)]
+pub struct Merge {
+    pub added: Vec<PathBuf>,
+    pub unchanged: Vec<PathBuf>,
+    /// Files that exist with other contents, which are kept as they are.
+    pub conflicts: Vec<PathBuf>,
+}
+
+impl Bundle {
+    /// Collects the puzzle data of a store, or of a single year, with the styles it refers to.
+    pub fn collect(store: &dyn PuzzleStore, year: Option<u32>) -> Result<Self, Error> {
+        let mut files = BTreeMap::new();
+        for path in store.files(Path::new("")) {
+            if is_puzzle_file(&path) && year.is_none_or(|year| year_of(&path) == Some(year)) {
+                let contents = store.read_file(&path)?;
+                files.insert(path, contents);
+            }
+        }
+
+        let styles: Vec<PathBuf> = files
+            .iter()
+            .filter(|(path, _)| path.file_name().is_some_and(|name| name == "styles.ref"))
+            .map(|(_, hash)| {
+                String::from_utf8(hash.clone()).map(|hash| stylesheet_path(hash.trim()))
+            })
+            .collect::<Result<_, _>>()?;
+        for path in styles {
+            if store.contains(&path) {
+                let contents = store.read_file(&path)?;
+                files.insert(path, contents);
+            }
+        }
+
+        Ok(Self { files })
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Encrypts the bundle, deriving its key from `password` with Argon2id since bundles are
+    /// meant to be handed to others.
+    pub fn encrypt(&self, password: &str) -> Result<Vec<u8>, Error> {
+        let archive = Archive {
+            version: BUNDLE_VERSION,
+            manifest: self
+                .files
+                .iter()
+                .map(|(path, contents)| {
+                    (path_name(path), blake3::hash(contents).to_hex().to_string())
+                })
+                .collect(),
+            files: self
+                .files
+                .iter()
+                .map(|(path, contents)| (path_name(path), STANDARD.encode(contents)))
+                .collect(),
+        };
+        let text = toml::to_string(&archive).map_err(|_| Error::Parse)?;
+        let key = SaltedKey::with_kdf(password, Kdf::ARGON2ID)?;
+        Ok(key.encrypt(text.as_bytes(), ASSOCIATED_DATA)?)
+    }
+
+    /// Decrypts a bundle, checking every file against the manifest.
+    pub fn decrypt(password: &str, encrypted: &[u8]) -> Result<Self, Error> {
+        let text = SaltedKey::extract(password, encrypted)?.decrypt(encrypted, ASSOCIATED_DATA)?;
+        let archive: Archive =
+            toml::from_str(&String::from_utf8(text)?).map_err(|_| Error::Parse)?;
+        Self::from_archive(archive)
+    }
+
+    fn from_archive(archive: Archive) -> Result<Self, Error> {
+        if archive.version != BUNDLE_VERSION || !archive.manifest.keys().eq(archive.files.keys()) {
+            return Err(Error::Parse);
+        }
+
+        let mut files = BTreeMap::new();
+        for (name, encoded) in archive.files {
+            let path = PathBuf::from(&name);
+            let contents = STANDARD.decode(encoded).map_err(|_| Error::Parse)?;
+            let valid_path = is_puzzle_file(&path) || path.parent() == Some(Path::new(STYLES_DIR));
+            if !valid_path || blake3::hash(&contents).to_hex().as_str() != archive.manifest[&name] {
+                return Err(Error::Parse);
+            }
+            files.insert(path, contents);
+        }
+        Ok(Self { files })
+    }
+
+    /// Writes the files of the bundle that are missing from a store. Files that already exist
+    /// are never replaced, and are reported as conflicts if their contents differ.
+    pub fn merge_into(&self, store: &dyn PuzzleStore) -> Result<Merge, Error> {
+        let mut merge = Merge::default();
+        for (path, contents) in &self.files {
+            if !store.contains(path) {
+                store.write_file(path, contents)?;
+                merge.added.push(path.clone());
+            } else if store.read_file(path)? == *contents {
+                merge.unchanged.push(path.clone());
+            } else {
+                merge.conflicts.push(path.clone());
+            }
+        }
+        Ok(merge)
+    }
+}
+
+/// The year a file belongs to, from the first directory of its path.
+fn year_of(path: &Path) -> Option<u32> {
+    match path.components().next()? {
+        Component::Normal(name) => name.to_str()?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Whether a path is shareable data of a puzzle, which also rules out paths leaving the store.
+fn is_puzzle_file(path: &Path) -> bool {
+    year_of(path).is_some()
+        && path.components().count() > 1
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        && path.file_name().is_some_and(|name| name != LEDGER_FILE)
+}
+
+fn path_name(path: &Path) -> String {
+    path.iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PuzzleId, api::Ledger, storage::MemoryStore};
+
+    fn store() -> MemoryStore {
+        let store = MemoryStore::default();
+        let first = PuzzleId::new(2024, 1, 1);
+        let second = PuzzleId::new(2025, 1, 1);
+        for id in [&first, &second] {
+            store.save_test(id, 1, "in", "out").unwrap();
+            store.save_input(id, 1, "input", Some("answer")).unwrap();
+            store.save_ledger(id, &Ledger::default()).unwrap();
+        }
+        store
+            .save_description(&first, Some("first"), Some("a {}"))
+            .unwrap();
+        store
+            .save_description(&second, Some("second"), Some("b {}"))
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn exports_a_year() {
+        let bundle = Bundle::collect(&store(), Some(2024)).unwrap();
+        let encrypted = bundle.encrypt("bundle-key").unwrap();
+        assert!(Bundle::decrypt("wrong", &encrypted).is_err());
+
+        let imported = MemoryStore::default();
+        let merge = Bundle::decrypt("bundle-key", &encrypted)
+            .unwrap()
+            .merge_into(&imported)
+            .unwrap();
+
+        let id = PuzzleId::new(2024, 1, 1);
+        assert_eq!(merge.added.len(), bundle.len());
+        assert_eq!(imported.read_input(&id).unwrap()[0].expected, "answer");
+        assert_eq!(imported.get_description(&id).unwrap().styles, "a {}");
+        assert_eq!(imported.files(Path::new("styles")).len(), 1);
+        assert!(imported.files(Path::new("2025")).is_empty());
+        assert!(!imported.contains(Path::new("2024/01/ledger.toml")));
+    }
+
+    #[test]
+    fn rejects_excessive_key_derivation() {
+        let mut encrypted = Bundle::collect(&store(), Some(2024))
+            .unwrap()
+            .encrypt("bundle-key")
+            .unwrap();
+        // The Argon2id memory follows the magic, version and KDF id
+        encrypted[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Bundle::decrypt("bundle-key", &encrypted).is_err());
+    }
+
+    #[test]
+    fn reports_conflicts() {
+        let bundle = Bundle::collect(&store(), None).unwrap();
+        let target = store();
+        let id = PuzzleId::new(2025, 1, 1);
+        target.save_test(&id, 1, "in", "changed").unwrap();
+
+        let merge = bundle.merge_into(&target).unwrap();
+        assert!(merge.added.is_empty());
+        assert_eq!(
+            merge.conflicts,
+            [PathBuf::from("2025/01/part_1/tests/test_01.out")]
+        );
+        assert_eq!(target.read_tests(&id).unwrap()[0].expected, "changed");
+    }
+
+    #[test]
+    fn rejects_tampered_archives() {
+        let archive = |name: &str, contents: &[u8], hash: &[u8]| Archive {
+            version: BUNDLE_VERSION,
+            manifest: BTreeMap::from([(name.to_string(), blake3::hash(hash).to_hex().to_string())]),
+            files: BTreeMap::from([(name.to_string(), STANDARD.encode(contents))]),
+        };
+
+        let name = "2024/01/meta.toml";
+        assert!(Bundle::from_archive(archive(name, b"a", b"a")).is_ok());
+        assert!(Bundle::from_archive(archive(name, b"a", b"b")).is_err());
+        assert!(Bundle::from_archive(archive("2024/../../meta.toml", b"a", b"a")).is_err());
+        assert!(Bundle::from_archive(archive("2024/01/ledger.toml", b"a", b"a")).is_err());
+    }
+}
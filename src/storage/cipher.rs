@@ -1,89 +1,418 @@
 use aes_gcm_siv::{
     Aes256GcmSiv, Error, KeyInit, Nonce,
-    aead::{Aead, OsRng, generic_array::GenericArray, rand_core::RngCore},
+    aead::{Aead, OsRng, Payload, generic_array::GenericArray, rand_core::RngCore},
 };
+use argon2::{Algorithm, Argon2, Params, Version};
 use blake3::Hasher;
+use std::{collections::HashMap, fmt, sync::Mutex};
 
 const SALT_LENGTH: usize = 32;
 const NONCE_LENGTH: usize = 12;
 const KEY_LENGTH: usize = 32;
 
+/// Starts every file in the versioned format. Older files have no header and start with the salt.
+const MAGIC: &[u8; 4] = b"AOCE";
+/// The version of the format written by this version of the code. Version 3 encrypts each file
+/// with a subkey of the key derived from the password, version 2 authenticates associated data,
+/// such as the path of the file, and version 1 does not.
+const VERSION: u8 = 3;
+/// The first version that authenticates associated data.
+const BOUND_VERSION: u8 = 2;
+/// The first version that derives a subkey for each file from the associated data.
+const SUBKEY_VERSION: u8 = 3;
+
+/// Limits on the Argon2id parameters read from headers, so that a crafted file cannot make
+/// deriving its key take unbounded memory or time.
+const MAX_ARGON2_MEMORY: u32 = 1024 * 1024;
+const MAX_ARGON2_ITERATIONS: u32 = 10;
+const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+const KDF_BLAKE3: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+
+/// How the key is derived from the password and salt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kdf {
+    /// A single blake3 pass. The encrypted data has minimal security requirements as it consists
+    /// solely of puzzle data that is publicly available, so this is the fast default, but it is
+    /// cheap to brute-force if the password is weak.
+    Blake3,
+    /// Argon2id, with `memory` in KiB.
+    Argon2id {
+        memory: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+impl Kdf {
+    /// Argon2id with the parameters recommended by OWASP.
+    pub const ARGON2ID: Self = Self::Argon2id {
+        memory: 19 * 1024,
+        iterations: 2,
+        parallelism: 1,
+    };
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blake3" => Some(Self::Blake3),
+            "argon2id" => Some(Self::ARGON2ID),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Blake3 => "blake3",
+            Self::Argon2id { .. } => "argon2id",
+        }
+    }
+
+    fn encode(self, header: &mut Vec<u8>) {
+        match self {
+            Self::Blake3 => header.push(KDF_BLAKE3),
+            Self::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                header.push(KDF_ARGON2ID);
+                header.extend(memory.to_le_bytes());
+                header.extend(iterations.to_le_bytes());
+                header.extend(parallelism.to_le_bytes());
+            }
+        }
+    }
+
+    /// Reads the KDF at the start of `bytes`, and returns it with the number of bytes read.
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)> {
+        let param = |index: usize| {
+            let start = 1 + 4 * index;
+            Some(u32::from_le_bytes(
+                bytes.get(start..start + 4)?.try_into().ok()?,
+            ))
+        };
+        match *bytes.first()? {
+            KDF_BLAKE3 => Some((Self::Blake3, 1)),
+            KDF_ARGON2ID => {
+                let kdf = Self::Argon2id {
+                    memory: param(0)?,
+                    iterations: param(1)?,
+                    parallelism: param(2)?,
+                };
+                kdf.is_within_limits().then_some((kdf, 13))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_within_limits(self) -> bool {
+        match self {
+            Self::Blake3 => true,
+            Self::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                memory <= MAX_ARGON2_MEMORY
+                    && iterations <= MAX_ARGON2_ITERATIONS
+                    && parallelism <= MAX_ARGON2_PARALLELISM
+            }
+        }
+    }
+
+    fn derive(self, password: &[u8], salt: &[u8]) -> Result<[u8; KEY_LENGTH], Error> {
+        if !self.is_within_limits() {
+            return Err(Error);
+        }
+        let mut key = [0u8; KEY_LENGTH];
+        match self {
+            Self::Blake3 => {
+                let mut hasher = Hasher::new();
+                hasher.update(password);
+                hasher.update(salt);
+                key = *hasher.finalize().as_bytes();
+            }
+            Self::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                let params = Params::new(memory, iterations, parallelism, Some(KEY_LENGTH))
+                    .map_err(|_| Error)?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password, salt, &mut key)
+                    .map_err(|_| Error)?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// The header of a file in the versioned format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Header {
+    version: u8,
+    kdf: Kdf,
+}
+
+impl Header {
+    fn binds_data(self) -> bool {
+        self.version >= BOUND_VERSION
+    }
+
+    fn derives_subkeys(self) -> bool {
+        self.version >= SUBKEY_VERSION
+    }
+}
+
+/// Keys already derived from a password, by KDF and salt. Files that share a salt, such as those
+/// of a store in the current format, then only run the KDF once.
+#[derive(Default)]
+pub struct KeyCache {
+    keys: Mutex<HashMap<KeyId, [u8; KEY_LENGTH]>>,
+}
+
+type KeyId = (Kdf, [u8; SALT_LENGTH]);
+
+impl KeyCache {
+    fn derive(
+        &self,
+        kdf: Kdf,
+        password: &str,
+        salt: [u8; SALT_LENGTH],
+    ) -> Result<[u8; KEY_LENGTH], Error> {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(key) = keys.get(&(kdf, salt)) {
+            return Ok(*key);
+        }
+        let key = kdf.derive(password.as_bytes(), &salt)?;
+        keys.insert((kdf, salt), key);
+        Ok(key)
+    }
+}
+
+impl fmt::Debug for KeyCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyCache").finish_non_exhaustive()
+    }
+}
+
+/// The parts of an encrypted file in front of the ciphertext.
+struct Layout {
+    /// `None` for headerless files.
+    header: Option<Header>,
+    /// Where the salt starts, after the header.
+    offset: usize,
+}
+
+impl Layout {
+    fn parse(encrypted: &[u8]) -> Result<Self, Error> {
+        let layout = match encrypted.strip_prefix(MAGIC) {
+            Some(rest) => {
+                let (&version, rest) = rest.split_first().ok_or(Error)?;
+                if version == 0 || version > VERSION {
+                    return Err(Error);
+                }
+                let (kdf, length) = Kdf::decode(rest).ok_or(Error)?;
+                Self {
+                    header: Some(Header { version, kdf }),
+                    offset: MAGIC.len() + 1 + length,
+                }
+            }
+            None => Self {
+                header: None,
+                offset: 0,
+            },
+        };
+        if encrypted.len() < layout.offset + SALT_LENGTH + NONCE_LENGTH {
+            return Err(Error);
+        }
+        Ok(layout)
+    }
+
+    fn ciphertext(&self) -> usize {
+        self.offset + SALT_LENGTH + NONCE_LENGTH
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SaltedKey {
     key: [u8; KEY_LENGTH],
     salt: [u8; SALT_LENGTH],
-    nonce: [u8; NONCE_LENGTH],
+    /// The header of the file the key was extracted from, which is kept when re-encrypting it.
+    /// `None` for headerless files.
+    header: Option<Header>,
 }
 
 impl SaltedKey {
     #[must_use]
     pub fn new(password: &str) -> Self {
+        Self::with_kdf(password, Kdf::Blake3).unwrap()
+    }
+
+    /// Creates a key with a fresh salt, derived with the given KDF.
+    pub fn with_kdf(password: &str, kdf: Kdf) -> Result<Self, Error> {
         let mut salt = [0u8; SALT_LENGTH];
         OsRng.fill_bytes(&mut salt);
 
-        let mut nonce = [0u8; NONCE_LENGTH];
-        OsRng.fill_bytes(&mut nonce);
+        let key = kdf.derive(password.as_bytes(), &salt)?;
+        Ok(Self {
+            key,
+            salt,
+            header: Some(Header {
+                version: VERSION,
+                kdf,
+            }),
+        })
+    }
 
-        let key = Self::derive_key(password.as_bytes(), &salt);
-        Self { key, salt, nonce }
+    /// Like [`SaltedKey::with_kdf`], in the last format that does not authenticate associated
+    /// data. Stores that still hold such files write new ones in it too, until they are migrated.
+    pub fn unbound_with_kdf(password: &str, kdf: Kdf) -> Result<Self, Error> {
+        let mut key = Self::with_kdf(password, kdf)?;
+        key.header = Some(Header {
+            version: BOUND_VERSION - 1,
+            kdf,
+        });
+        Ok(key)
     }
 
-    #[must_use]
-    pub fn extract(password: &str, encrypted: &[u8]) -> Self {
-        let salt = &encrypted[0..SALT_LENGTH];
-        let nonce = &encrypted[SALT_LENGTH..SALT_LENGTH + NONCE_LENGTH];
-        let key = Self::derive_key(password.as_bytes(), salt);
+    /// Derives the key of an encrypted file, which may be in an older format.
+    pub fn extract(password: &str, encrypted: &[u8]) -> Result<Self, Error> {
+        Self::extract_cached(password, encrypted, &KeyCache::default())
+    }
 
-        Self {
-            key,
-            salt: salt.try_into().unwrap(),
-            nonce: nonce.try_into().unwrap(),
-        }
+    /// Like [`SaltedKey::extract`], reusing keys already derived for the same salt.
+    pub fn extract_cached(
+        password: &str,
+        encrypted: &[u8],
+        cache: &KeyCache,
+    ) -> Result<Self, Error> {
+        let layout = Layout::parse(encrypted)?;
+        let salt = encrypted[layout.offset..layout.offset + SALT_LENGTH]
+            .try_into()
+            .unwrap();
+        let kdf = layout.header.map_or(Kdf::Blake3, |header| header.kdf);
+
+        Ok(Self {
+            key: cache.derive(kdf, password, salt)?,
+            salt,
+            header: layout.header,
+        })
+    }
+
+    /// The KDF of an encrypted file, without deriving its key.
+    pub fn kdf_of(encrypted: &[u8]) -> Result<Kdf, Error> {
+        Ok(Layout::parse(encrypted)?
+            .header
+            .map_or(Kdf::Blake3, |header| header.kdf))
+    }
+
+    /// Whether an encrypted file authenticates its associated data. Files in older formats
+    /// decrypt with any associated data.
+    pub fn is_bound(encrypted: &[u8]) -> Result<bool, Error> {
+        Ok(Layout::parse(encrypted)?
+            .header
+            .is_some_and(Header::binds_data))
     }
 
-    /// Reuse salt/nonce from existing encrypted data if available, otherwise generate new ones.
-    /// This prevents unnecessary git churn when re-encrypting unchanged content.
+    /// Reuse the salt of existing encrypted data if available, otherwise generate a new one.
+    /// As nonces are derived from the content, this prevents unnecessary git churn when
+    /// re-encrypting unchanged content.
     #[must_use]
     pub fn reuse_or_new(password: &str, existing_encrypted: Option<&[u8]>) -> Self {
+        Self::reuse_or_with_kdf(password, existing_encrypted, Kdf::Blake3).unwrap()
+    }
+
+    /// Like [`SaltedKey::reuse_or_new`], deriving new keys with the given KDF.
+    pub fn reuse_or_with_kdf(
+        password: &str,
+        existing_encrypted: Option<&[u8]>,
+        kdf: Kdf,
+    ) -> Result<Self, Error> {
         if let Some(encrypted) = existing_encrypted
-            && encrypted.len() >= SALT_LENGTH + NONCE_LENGTH
+            && let Ok(key) = Self::extract(password, encrypted)
         {
-            // Reuse existing salt and nonce
-            return Self::extract(password, encrypted);
+            // Reuse existing salt, and with it the key
+            return Ok(key);
         }
-        Self::new(password)
+        Self::with_kdf(password, kdf)
     }
 
-    pub fn encrypt(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
-        let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&self.key));
-        let cipher_text = cipher.encrypt(Nonce::from_slice(&self.nonce), input)?;
-
-        Ok(self
-            .salt
-            .iter()
-            .chain(self.nonce.iter())
-            .chain(cipher_text.iter())
-            .copied()
-            .collect())
+    /// Encrypts `input`, authenticating `associated_data` unless the key is for an older format.
+    /// Encryption is deterministic: the same input and associated data always produce the same
+    /// output, and any change to them produces a different nonce.
+    pub fn encrypt(&self, input: &[u8], associated_data: &[u8]) -> Result<Vec<u8>, Error> {
+        let payload = self.payload(input, associated_data);
+        let nonce = self.nonce(payload.msg, payload.aad);
+        let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&self.file_key(associated_data)));
+        let cipher_text = cipher.encrypt(Nonce::from_slice(&nonce), payload)?;
+
+        let mut output = Vec::new();
+        if let Some(header) = self.header {
+            output.extend(MAGIC);
+            output.push(header.version);
+            header.kdf.encode(&mut output);
+        }
+        output.extend(self.salt);
+        output.extend(nonce);
+        output.extend(cipher_text);
+        Ok(output)
     }
 
-    pub fn decrypt(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
-        let cipher_text = &input[SALT_LENGTH + NONCE_LENGTH..];
+    /// Decrypts `input`, which fails if it was encrypted with other associated data.
+    pub fn decrypt(&self, input: &[u8], associated_data: &[u8]) -> Result<Vec<u8>, Error> {
+        let layout = Layout::parse(input)?;
+        let nonce = &input[layout.offset + SALT_LENGTH..layout.ciphertext()];
+        let cipher_text = &input[layout.ciphertext()..];
 
-        let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&self.key));
+        let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&self.file_key(associated_data)));
 
-        cipher.decrypt(Nonce::from_slice(&self.nonce), cipher_text)
+        cipher.decrypt(
+            Nonce::from_slice(nonce),
+            self.payload(cipher_text, associated_data),
+        )
     }
 
-    fn derive_key(password: &[u8], salt: &[u8]) -> [u8; KEY_LENGTH] {
-        // Blake3 is used instead of Argon2 for performance reasons.
-        // The encrypted data has minimal security requirements as it consists
-        // solely of puzzle data that is publicly available.
-        let mut hasher = Hasher::new();
-        hasher.update(password);
-        hasher.update(salt);
+    /// The key that encrypts a file. In the current format it is a subkey for the associated data,
+    /// so that files sharing a salt, and with it the expensive part of deriving their key, are
+    /// still encrypted with different keys.
+    fn file_key(&self, associated_data: &[u8]) -> [u8; KEY_LENGTH] {
+        if !self.header.is_some_and(Header::derives_subkeys) {
+            return self.key;
+        }
+        let mut hasher = Hasher::new_derive_key("aoc 2026-10-18 file key");
+        hasher.update(&self.key);
+        hasher.update(associated_data);
         *hasher.finalize().as_bytes()
     }
+
+    /// Derives the nonce from a keyed hash of the content, using a subkey so the hash never sees
+    /// the encryption key itself. The associated data is length-prefixed so that it cannot be
+    /// confused with the start of the input.
+    fn nonce(&self, input: &[u8], associated_data: &[u8]) -> [u8; NONCE_LENGTH] {
+        let subkey = blake3::derive_key("aoc 2024-12-01 nonce", &self.key);
+        let mut hasher = Hasher::new_keyed(&subkey);
+        hasher.update(&(associated_data.len() as u64).to_le_bytes());
+        hasher.update(associated_data);
+        hasher.update(input);
+
+        let mut nonce = [0u8; NONCE_LENGTH];
+        hasher.finalize_xof().fill(&mut nonce);
+        nonce
+    }
+
+    fn payload<'a>(&self, msg: &'a [u8], associated_data: &'a [u8]) -> Payload<'a, 'a> {
+        let aad = if self.header.is_some_and(Header::binds_data) {
+            associated_data
+        } else {
+            &[]
+        };
+        Payload { msg, aad }
+    }
 }
 
 #[cfg(test)]
@@ -95,10 +424,11 @@ mod tests {
     #[quickcheck]
     fn identity(input: Vec<u8>, password: String) {
         let key = SaltedKey::new(&password);
-        let encrypted = key.encrypt(&input).unwrap();
-        let decrypted1 = key.decrypt(&encrypted).unwrap();
+        let encrypted = key.encrypt(&input, &[]).unwrap();
+        let decrypted1 = key.decrypt(&encrypted, &[]).unwrap();
         let decrypted2 = SaltedKey::extract(&password, &encrypted)
-            .decrypt(&encrypted)
+            .unwrap()
+            .decrypt(&encrypted, &[])
             .unwrap();
 
         assert_eq!(decrypted1, input);
@@ -109,10 +439,10 @@ mod tests {
     #[quickcheck]
     fn reuse_produces_identical_encryption(data: Vec<u8>, password: String) {
         let key1 = SaltedKey::new(&password);
-        let encrypted1 = key1.encrypt(&data).unwrap();
+        let encrypted1 = key1.encrypt(&data, &[]).unwrap();
 
         let key2 = SaltedKey::reuse_or_new(&password, Some(&encrypted1));
-        let encrypted2 = key2.encrypt(&data).unwrap();
+        let encrypted2 = key2.encrypt(&data, &[]).unwrap();
 
         assert_eq!(encrypted1, encrypted2);
         assert_eq!(key1, key2);
@@ -122,10 +452,10 @@ mod tests {
     #[quickcheck]
     fn new_keys_produce_different_encryptions(data: Vec<u8>, password: String) {
         let key1 = SaltedKey::reuse_or_new(&password, None);
-        let encrypted1 = key1.encrypt(&data).unwrap();
+        let encrypted1 = key1.encrypt(&data, &[]).unwrap();
 
         let key2 = SaltedKey::reuse_or_new(&password, None);
-        let encrypted2 = key2.encrypt(&data).unwrap();
+        let encrypted2 = key2.encrypt(&data, &[]).unwrap();
 
         assert_ne!(encrypted1, encrypted2);
         assert_ne!(key1, key2);
@@ -135,35 +465,228 @@ mod tests {
     #[quickcheck]
     fn reuse_preserves_decryptability(data1: Vec<u8>, data2: Vec<u8>, password: String) {
         let key1 = SaltedKey::new(&password);
-        let encrypted = key1.encrypt(&data1).unwrap();
+        let encrypted = key1.encrypt(&data1, &[]).unwrap();
 
         let key2 = SaltedKey::reuse_or_new(&password, Some(&encrypted));
-        let new_encrypted = key2.encrypt(&data2).unwrap();
+        let new_encrypted = key2.encrypt(&data2, &[]).unwrap();
 
-        let decrypted_original = key2.decrypt(&encrypted).unwrap();
+        let decrypted_original = key2.decrypt(&encrypted, &[]).unwrap();
         assert_eq!(decrypted_original, data1);
 
-        let decrypted_new = key2.decrypt(&new_encrypted).unwrap();
+        let decrypted_new = key2.decrypt(&new_encrypted, &[]).unwrap();
         assert_eq!(decrypted_new, data2);
     }
 
+    #[allow(clippy::needless_pass_by_value)]
+    #[quickcheck]
+    fn changed_content_gets_fresh_nonce(data1: Vec<u8>, data2: Vec<u8>, password: String) {
+        let key1 = SaltedKey::new(&password);
+        let encrypted1 = key1.encrypt(&data1, &[]).unwrap();
+
+        let key2 = SaltedKey::reuse_or_new(&password, Some(&encrypted1));
+        let encrypted2 = key2.encrypt(&data2, &[]).unwrap();
+
+        assert_eq!(key1, key2);
+        assert_eq!(nonce(&encrypted1) == nonce(&encrypted2), data1 == data2);
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    #[quickcheck]
+    fn nonce_depends_on_associated_data(
+        data: Vec<u8>,
+        password: String,
+        path: String,
+        other: String,
+    ) {
+        let key = SaltedKey::new(&password);
+        let encrypted1 = key.encrypt(&data, path.as_bytes()).unwrap();
+        let encrypted2 = key.encrypt(&data, other.as_bytes()).unwrap();
+
+        assert_eq!(nonce(&encrypted1) == nonce(&encrypted2), path == other);
+    }
+
     #[allow(clippy::needless_pass_by_value)]
     #[quickcheck]
     fn deterministic_reencryption(data: Vec<u8>, password: String) {
         let key = SaltedKey::new(&password);
-        let encrypted1 = key.encrypt(&data).unwrap();
+        let encrypted1 = key.encrypt(&data, &[]).unwrap();
 
         let reused_key = SaltedKey::reuse_or_new(&password, Some(&encrypted1));
-        let encrypted2 = reused_key.encrypt(&data).unwrap();
+        let encrypted2 = reused_key.encrypt(&data, &[]).unwrap();
 
         assert_eq!(encrypted1, encrypted2);
 
         let encrypted3 = SaltedKey::reuse_or_new(&password, Some(&encrypted2))
-            .encrypt(&data)
+            .encrypt(&data, &[])
             .unwrap();
         assert_eq!(encrypted1, encrypted3);
     }
 
+    #[allow(clippy::needless_pass_by_value)]
+    #[quickcheck]
+    fn reads_headerless_files(data: Vec<u8>, password: String) {
+        let key = SaltedKey::unbound_with_kdf(&password, Kdf::Blake3).unwrap();
+        let encrypted = key.encrypt(&data, &[]).unwrap();
+        let headerless = &encrypted[MAGIC.len() + 2..];
+
+        let key = SaltedKey::extract(&password, headerless).unwrap();
+        assert_eq!(key.decrypt(headerless, &[]).unwrap(), data);
+        // Headerless files stay headerless when re-encrypted in place
+        assert_eq!(key.encrypt(&data, &[]).unwrap(), headerless);
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    #[quickcheck]
+    fn binds_associated_data(data: Vec<u8>, password: String, path: String, other: String) {
+        let key = SaltedKey::new(&password);
+        let encrypted = key.encrypt(&data, path.as_bytes()).unwrap();
+
+        assert!(SaltedKey::is_bound(&encrypted).unwrap());
+        assert_eq!(key.decrypt(&encrypted, path.as_bytes()).unwrap(), data);
+        assert_eq!(
+            key.decrypt(&encrypted, other.as_bytes()).is_ok(),
+            path == other
+        );
+    }
+
+    #[test]
+    fn older_versions_ignore_associated_data() {
+        let mut key = SaltedKey::new("password");
+        key.header = Some(Header {
+            version: 1,
+            kdf: Kdf::Blake3,
+        });
+        let encrypted = key.encrypt(b"test", &[]).unwrap();
+
+        let key = SaltedKey::extract("password", &encrypted).unwrap();
+        assert!(!SaltedKey::is_bound(&encrypted).unwrap());
+        assert_eq!(key.decrypt(&encrypted, b"any/path").unwrap(), b"test");
+        assert_eq!(key.encrypt(b"test", b"any/path").unwrap(), encrypted);
+    }
+
+    #[test]
+    fn writes_unbound_files() {
+        let key = SaltedKey::unbound_with_kdf("password", Kdf::Blake3).unwrap();
+        let encrypted = key.encrypt(b"data", b"path").unwrap();
+        assert!(encrypted.starts_with(b"AOCE\x01\x00"));
+        assert!(!SaltedKey::is_bound(&encrypted).unwrap());
+        assert_eq!(key.decrypt(&encrypted, b"other").unwrap(), b"data");
+    }
+
+    #[test]
+    fn derives_keys_with_argon2id() {
+        let kdf = Kdf::Argon2id {
+            memory: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let key = SaltedKey::with_kdf("password", kdf).unwrap();
+        let encrypted = key.encrypt(b"test", &[]).unwrap();
+
+        assert_eq!(SaltedKey::kdf_of(&encrypted).unwrap(), kdf);
+        let extracted = SaltedKey::extract("password", &encrypted).unwrap();
+        assert_eq!(extracted, key);
+        assert_eq!(extracted.decrypt(&encrypted, &[]).unwrap(), b"test");
+        assert!(
+            SaltedKey::extract("wrong", &encrypted)
+                .unwrap()
+                .decrypt(&encrypted, &[])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn reads_version_2_files() {
+        let mut key = SaltedKey::new("password");
+        key.header = Some(Header {
+            version: 2,
+            kdf: Kdf::Blake3,
+        });
+        let encrypted = key.encrypt(b"test", b"path").unwrap();
+
+        let key = SaltedKey::extract("password", &encrypted).unwrap();
+        assert!(SaltedKey::is_bound(&encrypted).unwrap());
+        assert_eq!(key.decrypt(&encrypted, b"path").unwrap(), b"test");
+        assert!(key.decrypt(&encrypted, b"other").is_err());
+    }
+
+    #[test]
+    fn encrypts_files_with_subkeys() {
+        let key = SaltedKey::new("password");
+        let encrypted = key.encrypt(b"test", b"path").unwrap();
+        assert!(encrypted.starts_with(b"AOCE\x03\x00"));
+
+        // The key derived from the password alone does not decrypt the file
+        let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&key.key));
+        let payload = Payload {
+            msg: &encrypted[Layout::parse(&encrypted).unwrap().ciphertext()..],
+            aad: b"path",
+        };
+        assert!(
+            cipher
+                .decrypt(Nonce::from_slice(nonce(&encrypted)), payload)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn derives_shared_keys_once() {
+        let cache = KeyCache::default();
+        let key = SaltedKey::with_kdf("password", Kdf::Blake3).unwrap();
+        let first = key.encrypt(b"first", b"a").unwrap();
+        let second = key.encrypt(b"second", b"b").unwrap();
+
+        let first_key = SaltedKey::extract_cached("password", &first, &cache).unwrap();
+        let second_key = SaltedKey::extract_cached("password", &second, &cache).unwrap();
+        assert_eq!(first_key, second_key);
+        assert_eq!(cache.keys.lock().unwrap().len(), 1);
+        assert_eq!(second_key.decrypt(&second, b"b").unwrap(), b"second");
+    }
+
+    #[test]
+    fn rejects_excessive_argon2id_params() {
+        let kdf = Kdf::Argon2id {
+            memory: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let encrypted = SaltedKey::with_kdf("password", kdf)
+            .unwrap()
+            .encrypt(b"test", &[])
+            .unwrap();
+        // memory, iterations and parallelism follow the KDF id
+        for (offset, value) in [
+            (0, MAX_ARGON2_MEMORY),
+            (4, MAX_ARGON2_ITERATIONS),
+            (8, MAX_ARGON2_PARALLELISM),
+        ] {
+            let mut crafted = encrypted.clone();
+            let start = MAGIC.len() + 2 + offset;
+            crafted[start..start + 4].copy_from_slice(&(value + 1).to_le_bytes());
+            assert!(SaltedKey::extract("password", &crafted).is_err());
+            assert!(SaltedKey::kdf_of(&crafted).is_err());
+        }
+
+        let kdf = Kdf::Argon2id {
+            memory: u32::MAX,
+            iterations: 1,
+            parallelism: 1,
+        };
+        assert!(SaltedKey::with_kdf("password", kdf).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let mut encrypted = SaltedKey::new("password").encrypt(b"test", &[]).unwrap();
+        encrypted[MAGIC.len()] = VERSION + 1;
+        assert!(SaltedKey::extract("password", &encrypted).is_err());
+    }
+
+    fn nonce(encrypted: &[u8]) -> &[u8] {
+        let layout = Layout::parse(encrypted).unwrap();
+        &encrypted[layout.offset + SALT_LENGTH..layout.ciphertext()]
+    }
+
     #[test]
     fn reuse_with_invalid_data_generates_new_key() {
         let password = "password";
@@ -174,8 +697,8 @@ mod tests {
 
         // Should generate new key since data is invalid
         let data = b"test";
-        let encrypted = key.encrypt(data).unwrap();
-        let decrypted = key.decrypt(&encrypted).unwrap();
+        let encrypted = key.encrypt(data, &[]).unwrap();
+        let decrypted = key.decrypt(&encrypted, &[]).unwrap();
         assert_eq!(decrypted, data);
     }
 }
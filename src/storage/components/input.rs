@@ -1,10 +1,12 @@
-use super::{Error, PuzzleInput, read_encrypted, write_encrypted};
-use crate::{
-    PuzzleId,
-    storage::{ENC_EXTENSION, PUZZLES_ENC_DIR},
+use super::{Error, PuzzleInput, read_string};
+use crate::{PuzzleId, storage::PuzzleStore};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
 };
-use std::{collections::HashMap, path::Path};
-use walkdir::WalkDir;
+
+/// Input and expected output files, by id.
+type Pairs = HashMap<u32, (Option<String>, Option<String>)>;
 
 enum FileType {
     Input(u32),
@@ -14,10 +16,10 @@ enum FileType {
 fn parse_puzzle_filename(filename: &str) -> Option<FileType> {
     let rest = filename.strip_prefix("puzzle_")?;
 
-    if let Some(id) = rest.strip_suffix(&format!(".in.{ENC_EXTENSION}")) {
+    if let Some(id) = rest.strip_suffix(".in") {
         let id = id.parse::<u32>().ok()?;
         Some(FileType::Input(id))
-    } else if let Some(id) = rest.strip_suffix(&format!(".out.{ENC_EXTENSION}")) {
+    } else if let Some(id) = rest.strip_suffix(".out") {
         let id = id.parse::<u32>().ok()?;
         Some(FileType::Output(id))
     } else {
@@ -25,46 +27,37 @@ fn parse_puzzle_filename(filename: &str) -> Option<FileType> {
     }
 }
 
-fn read_file_content(path: &Path, password: &str) -> Option<String> {
-    read_encrypted(path, password)
-        .ok()
-        .and_then(|bytes| String::from_utf8(bytes).ok())
-}
-
-fn collect_input_pairs(
+fn collect_input_pairs<S: PuzzleStore + ?Sized>(
+    store: &S,
     input_dir: &Path,
-    password: &str,
-) -> HashMap<u32, (Option<String>, Option<String>)> {
-    let mut inputs: HashMap<u32, (Option<String>, Option<String>)> = HashMap::new();
+) -> Result<Pairs, Error> {
+    let mut inputs: Pairs = HashMap::new();
 
-    for entry in WalkDir::new(input_dir)
-        .max_depth(1)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
+    for path in store.files(input_dir) {
+        if path.parent() != Some(input_dir) {
+            continue;
+        }
         let Some(filename) = path.file_name().and_then(|s| s.to_str()) else {
             continue;
         };
 
         match parse_puzzle_filename(filename) {
             Some(FileType::Input(id)) => {
-                let content = read_file_content(path, password);
+                let content = Some(read_string(store, &path)?);
                 inputs.entry(id).or_default().0 = content;
             }
             Some(FileType::Output(id)) => {
-                let content = read_file_content(path, password);
+                let content = Some(read_string(store, &path)?);
                 inputs.entry(id).or_default().1 = content;
             }
             None => {}
         }
     }
 
-    inputs
+    Ok(inputs)
 }
 
-fn build_puzzle_inputs(inputs: HashMap<u32, (Option<String>, Option<String>)>) -> Vec<PuzzleInput> {
+fn build_puzzle_inputs(inputs: Pairs) -> Vec<PuzzleInput> {
     let mut result: Vec<_> = inputs
         .into_iter()
         .filter_map(|(id, (input, expected))| {
@@ -80,31 +73,44 @@ fn build_puzzle_inputs(inputs: HashMap<u32, (Option<String>, Option<String>)>) -
     result
 }
 
-#[must_use]
-pub fn read_input(id: &PuzzleId, password: &str) -> Vec<PuzzleInput> {
-    let input_dir = Path::new(PUZZLES_ENC_DIR)
-        .join(format!("{}/{:02}/part_{}/inputs", id.year, id.day, id.part));
+fn input_dir(id: &PuzzleId) -> PathBuf {
+    PathBuf::from(format!("{}/{:02}/part_{}/inputs", id.year, id.day, id.part))
+}
+
+pub(in crate::storage) fn read_input<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+) -> Result<Vec<PuzzleInput>, Error> {
+    let inputs = collect_input_pairs(store, &input_dir(id))?;
+    Ok(build_puzzle_inputs(inputs))
+}
 
-    let inputs = collect_input_pairs(&input_dir, password);
-    build_puzzle_inputs(inputs)
+pub(in crate::storage) fn read_input_file<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+    input_id: u32,
+) -> Result<String, Error> {
+    read_string(
+        store,
+        &input_dir(id).join(format!("puzzle_{input_id:02}.in")),
+    )
 }
 
-pub fn save_input(
+pub(in crate::storage) fn save_input<S: PuzzleStore + ?Sized>(
+    store: &S,
     id: &PuzzleId,
     input_id: u32,
     input: &str,
     expected: Option<&str>,
-    password: &str,
 ) -> Result<(), Error> {
-    let input_dir = Path::new(PUZZLES_ENC_DIR)
-        .join(format!("{}/{:02}/part_{}/inputs", id.year, id.day, id.part));
+    let input_dir = input_dir(id);
 
-    let input_path = input_dir.join(format!("puzzle_{input_id:02}.in.{ENC_EXTENSION}"));
-    write_encrypted(&input_path, input.as_bytes(), password)?;
+    let input_path = input_dir.join(format!("puzzle_{input_id:02}.in"));
+    store.write_file(&input_path, input.as_bytes())?;
 
     if let Some(output) = expected {
-        let output_path = input_dir.join(format!("puzzle_{input_id:02}.out.{ENC_EXTENSION}"));
-        write_encrypted(&output_path, output.as_bytes(), password)?;
+        let output_path = input_dir.join(format!("puzzle_{input_id:02}.out"));
+        store.write_file(&output_path, output.as_bytes())?;
     }
 
     Ok(())
@@ -0,0 +1,33 @@
+use super::{Error, read_string};
+use crate::{PuzzleId, api::Ledger, storage::PuzzleStore};
+use std::path::PathBuf;
+
+/// The answers submitted for a day, which are personal and not shared in bundles.
+pub(in crate::storage) const LEDGER_FILE: &str = "ledger.toml";
+
+fn ledger_path(id: &PuzzleId) -> PathBuf {
+    PathBuf::from(format!("{}/{:02}/{LEDGER_FILE}", id.year, id.day))
+}
+
+/// Reads the answer ledger of a day, which is empty if nothing has been submitted yet.
+pub(in crate::storage) fn read_ledger<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+) -> Result<Ledger, Error> {
+    let path = ledger_path(id);
+    if !store.contains(&path) {
+        return Ok(Ledger::default());
+    }
+
+    let ledger: Ledger = toml::from_str(&read_string(store, &path)?).map_err(|_| Error::Parse)?;
+    Ok(ledger)
+}
+
+pub(in crate::storage) fn save_ledger<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+    ledger: &Ledger,
+) -> Result<(), Error> {
+    let contents = toml::to_string(ledger).map_err(|_| Error::Parse)?;
+    store.write_file(&ledger_path(id), contents.as_bytes())
+}
@@ -1,23 +1,46 @@
-use super::{Error, read_encrypted};
-use crate::{
-    PuzzleId,
-    storage::{ENC_EXTENSION, PUZZLES_ENC_DIR},
-};
+use super::Error;
+use crate::{PuzzleId, storage::PuzzleStore};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::PathBuf;
+use time::OffsetDateTime;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PuzzleMetadata {
     pub title: String,
+    /// Stars collected by the account, as last seen on the event calendar.
+    #[serde(default)]
+    pub stars: u32,
+    /// When the puzzle was last downloaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloaded_at: Option<OffsetDateTime>,
+    /// The page the puzzle was downloaded from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// When the puzzle page was last searched for accepted answers. Until then, answers are
+    /// looked for even if `stars` says the day is unsolved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answers_checked_at: Option<OffsetDateTime>,
 }
 
-pub fn read_metadata(id: &PuzzleId, password: &str) -> Result<PuzzleMetadata, Error> {
-    let meta_path = Path::new(PUZZLES_ENC_DIR)
-        .join(format!("{}/{:02}", id.year, id.day))
-        .join(format!("meta.toml.{ENC_EXTENSION}"));
+fn metadata_path(id: &PuzzleId) -> PathBuf {
+    PathBuf::from(format!("{}/{:02}/meta.toml", id.year, id.day))
+}
 
-    let bytes = read_encrypted(&meta_path, password)?;
+pub(in crate::storage) fn read_metadata<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+) -> Result<PuzzleMetadata, Error> {
+    let bytes = store.read_file(&metadata_path(id))?;
     let metadata: PuzzleMetadata =
         toml::from_str(&String::from_utf8_lossy(&bytes)).map_err(|_| Error::Parse)?;
     Ok(metadata)
 }
+
+pub(in crate::storage) fn save_metadata<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+    metadata: &PuzzleMetadata,
+) -> Result<(), Error> {
+    let contents = toml::to_string(metadata).map_err(|_| Error::Parse)?;
+    store.write_file(&metadata_path(id), contents.as_bytes())
+}
@@ -1,9 +1,9 @@
-use super::{Error, read_encrypted, write_encrypted};
-use crate::{
-    PuzzleId,
-    storage::{ENC_EXTENSION, PUZZLES_ENC_DIR},
-};
-use std::path::Path;
+use super::{Error, read_string};
+use crate::{PuzzleId, storage::PuzzleStore};
+use std::path::{Path, PathBuf};
+
+/// Directory for stylesheets, stored once by content hash and shared between puzzles.
+pub(in crate::storage) const STYLES_DIR: &str = "styles";
 
 #[derive(Clone, Debug)]
 pub struct Description {
@@ -11,15 +11,22 @@ pub struct Description {
     pub styles: String,
 }
 
-pub fn get_description(id: &PuzzleId, password: &str) -> Result<Description, Error> {
-    let puzzle_dir = Path::new(PUZZLES_ENC_DIR)
-        .join(format!("{}/{:02}/part_{}/puzzle", id.year, id.day, id.part));
+fn puzzle_dir(id: &PuzzleId) -> PathBuf {
+    PathBuf::from(format!("{}/{:02}/part_{}/puzzle", id.year, id.day, id.part))
+}
 
-    let description_path = puzzle_dir.join(format!("description.html.{ENC_EXTENSION}"));
-    let styles_path = puzzle_dir.join(format!("styles.css.{ENC_EXTENSION}"));
+pub(in crate::storage) fn stylesheet_path(hash: &str) -> PathBuf {
+    Path::new(STYLES_DIR).join(format!("{hash}.css"))
+}
+
+pub(in crate::storage) fn get_description<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+) -> Result<Description, Error> {
+    let puzzle_dir = puzzle_dir(id);
 
-    let description = String::from_utf8(read_encrypted(&description_path, password)?)?;
-    let styles = String::from_utf8(read_encrypted(&styles_path, password)?)?;
+    let description = read_string(store, &puzzle_dir.join("description.html"))?;
+    let styles = read_styles(store, &puzzle_dir)?;
 
     Ok(Description {
         description,
@@ -27,22 +34,43 @@ pub fn get_description(id: &PuzzleId, password: &str) -> Result<Description, Err
     })
 }
 
-pub fn save_description(
+/// Reads the stylesheet referenced by a puzzle, falling back to a copy stored next to it.
+fn read_styles<S: PuzzleStore + ?Sized>(store: &S, puzzle_dir: &Path) -> Result<String, Error> {
+    let reference_path = puzzle_dir.join("styles.ref");
+    let styles_path = if store.contains(&reference_path) {
+        let hash = read_string(store, &reference_path)?;
+        stylesheet_path(hash.trim())
+    } else {
+        puzzle_dir.join("styles.css")
+    };
+
+    read_string(store, &styles_path)
+}
+
+pub(in crate::storage) fn save_description<S: PuzzleStore + ?Sized>(
+    store: &S,
     id: &PuzzleId,
     description: Option<&str>,
     styles: Option<&str>,
-    password: &str,
 ) -> Result<(), Error> {
-    let puzzle_dir = Path::new(PUZZLES_ENC_DIR)
-        .join(format!("{}/{:02}/part_{}/puzzle", id.year, id.day, id.part));
+    let puzzle_dir = puzzle_dir(id);
 
     if let Some(html) = description {
-        let path = puzzle_dir.join(format!("description.html.{ENC_EXTENSION}"));
-        write_encrypted(&path, html.as_bytes(), password)?;
+        store.write_file(&puzzle_dir.join("description.html"), html.as_bytes())?;
     }
     if let Some(css) = styles {
-        let path = puzzle_dir.join(format!("styles.css.{ENC_EXTENSION}"));
-        write_encrypted(&path, css.as_bytes(), password)?;
+        let hash = blake3::hash(css.as_bytes()).to_hex();
+        let path = stylesheet_path(&hash);
+        if !store.contains(&path) {
+            store.write_file(&path, css.as_bytes())?;
+        }
+
+        store.write_file(&puzzle_dir.join("styles.ref"), hash.as_bytes())?;
+
+        let legacy_path = puzzle_dir.join("styles.css");
+        if store.contains(&legacy_path) {
+            store.remove_file(&legacy_path)?;
+        }
     }
 
     Ok(())
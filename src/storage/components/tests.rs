@@ -1,10 +1,12 @@
-use super::TestCase;
-use crate::{
-    PuzzleId,
-    storage::{ENC_EXTENSION, PUZZLES_ENC_DIR, components::read_encrypted},
+use super::{Error, TestCase, read_string};
+use crate::{PuzzleId, storage::PuzzleStore};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
 };
-use std::{collections::HashMap, path::Path};
-use walkdir::WalkDir;
+
+/// Input and expected output files, by id.
+type Pairs = HashMap<u32, (Option<String>, Option<String>)>;
 
 enum FileType {
     Input(u32),
@@ -14,10 +16,10 @@ enum FileType {
 fn parse_test_filename(filename: &str) -> Option<FileType> {
     let rest = filename.strip_prefix("test_")?;
 
-    if let Some(id) = rest.strip_suffix(&format!(".in.{ENC_EXTENSION}")) {
+    if let Some(id) = rest.strip_suffix(".in") {
         let id = id.parse::<u32>().ok()?;
         Some(FileType::Input(id))
-    } else if let Some(id) = rest.strip_suffix(&format!(".out.{ENC_EXTENSION}")) {
+    } else if let Some(id) = rest.strip_suffix(".out") {
         let id = id.parse::<u32>().ok()?;
         Some(FileType::Output(id))
     } else {
@@ -25,46 +27,34 @@ fn parse_test_filename(filename: &str) -> Option<FileType> {
     }
 }
 
-fn read_file_content(path: &Path, password: &str) -> Option<String> {
-    read_encrypted(path, password)
-        .ok()
-        .and_then(|bytes| String::from_utf8(bytes).ok())
-}
-
-fn collect_test_pairs(
-    test_dir: &Path,
-    password: &str,
-) -> HashMap<u32, (Option<String>, Option<String>)> {
-    let mut tests: HashMap<u32, (Option<String>, Option<String>)> = HashMap::new();
+fn collect_test_pairs<S: PuzzleStore + ?Sized>(store: &S, test_dir: &Path) -> Result<Pairs, Error> {
+    let mut tests: Pairs = HashMap::new();
 
-    for entry in WalkDir::new(test_dir)
-        .max_depth(1)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
+    for path in store.files(test_dir) {
+        if path.parent() != Some(test_dir) {
+            continue;
+        }
         let Some(filename) = path.file_name().and_then(|s| s.to_str()) else {
             continue;
         };
 
         match parse_test_filename(filename) {
             Some(FileType::Input(id)) => {
-                let content = read_file_content(path, password);
+                let content = Some(read_string(store, &path)?);
                 tests.entry(id).or_default().0 = content;
             }
             Some(FileType::Output(id)) => {
-                let content = read_file_content(path, password);
+                let content = Some(read_string(store, &path)?);
                 tests.entry(id).or_default().1 = content;
             }
             None => {}
         }
     }
 
-    tests
+    Ok(tests)
 }
 
-fn build_test_cases(tests: HashMap<u32, (Option<String>, Option<String>)>) -> Vec<TestCase> {
+fn build_test_cases(tests: Pairs) -> Vec<TestCase> {
     let mut result: Vec<_> = tests
         .into_iter()
         .filter_map(|(id, (input, expected))| {
@@ -80,10 +70,32 @@ fn build_test_cases(tests: HashMap<u32, (Option<String>, Option<String>)>) -> Ve
     result
 }
 
-pub fn read_tests(id: &PuzzleId, password: &str) -> Vec<TestCase> {
-    let test_dir = Path::new(PUZZLES_ENC_DIR)
-        .join(format!("{}/{:02}/part_{}/tests", id.year, id.day, id.part));
+fn test_dir(id: &PuzzleId) -> PathBuf {
+    PathBuf::from(format!("{}/{:02}/part_{}/tests", id.year, id.day, id.part))
+}
+
+pub(in crate::storage) fn read_tests<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+) -> Result<Vec<TestCase>, Error> {
+    let tests = collect_test_pairs(store, &test_dir(id))?;
+    Ok(build_test_cases(tests))
+}
+
+pub(in crate::storage) fn save_test<S: PuzzleStore + ?Sized>(
+    store: &S,
+    id: &PuzzleId,
+    test_id: u32,
+    input: &str,
+    expected: &str,
+) -> Result<(), Error> {
+    let test_dir = test_dir(id);
+
+    let input_path = test_dir.join(format!("test_{test_id:02}.in"));
+    store.write_file(&input_path, input.as_bytes())?;
+
+    let output_path = test_dir.join(format!("test_{test_id:02}.out"));
+    store.write_file(&output_path, expected.as_bytes())?;
 
-    let tests = collect_test_pairs(&test_dir, password);
-    build_test_cases(tests)
+    Ok(())
 }
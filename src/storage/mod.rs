@@ -1,11 +1,14 @@
+mod bundle;
 mod cipher;
 mod components;
+mod store;
 
-pub use cipher::SaltedKey;
-pub use components::{
-    Description, Puzzle, PuzzleInput, PuzzleMetadata, TestCase, decrypt_all, encrypt_all, get,
-    get_all, get_description, read_input, save_description, save_input,
-};
+use std::path::{Path, PathBuf};
+
+pub use bundle::{Bundle, Merge};
+pub use cipher::{Kdf, SaltedKey};
+pub use components::{Description, Error, Puzzle, PuzzleInput, PuzzleMetadata, TestCase};
+pub use store::{EncryptedStore, MemoryStore, PlaintextStore, PuzzleStore, copy_all};
 
 /// Directory containing plaintext puzzle files
 const PUZZLES_DIR: &str = "puzzles";
@@ -15,3 +18,12 @@ const ENC_EXTENSION: &str = "enc";
 
 /// Directory containing encrypted puzzle files
 const PUZZLES_ENC_DIR: &str = "puzzles.enc";
+
+/// Finds the nearest directory at or above `start` that contains `puzzles.enc`.
+#[must_use]
+pub fn find_data_dir(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(PUZZLES_ENC_DIR).is_dir())
+        .map(Path::to_path_buf)
+}
@@ -0,0 +1,299 @@
+use super::PuzzleStore;
+use crate::storage::{
+    ENC_EXTENSION, PUZZLES_ENC_DIR,
+    cipher::{Kdf, KeyCache, SaltedKey},
+    components::Error,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use walkdir::WalkDir;
+
+/// A known plaintext at the root of the store, to tell a wrong password from a damaged file.
+const KEY_CHECK: &str = "key.check";
+const KEY_CHECK_CONTENTS: &[u8] = b"Advent of Code";
+
+/// The `puzzles.enc` directory, where every file is encrypted with the same password.
+#[derive(Clone, Debug)]
+pub struct EncryptedStore {
+    root: PathBuf,
+    password: String,
+    /// How keys of new files are derived.
+    kdf: Kdf,
+    /// Whether files must authenticate their path, which is the case for new stores and once a
+    /// store is migrated. Otherwise a file could be replaced with one in an older format from
+    /// elsewhere.
+    bound: bool,
+    /// Keys derived from the password so far. Every file of a bound store shares the salt of the
+    /// key check record, so the KDF runs once per store rather than once per file.
+    keys: Arc<KeyCache>,
+}
+
+impl EncryptedStore {
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>, password: &str) -> Self {
+        Self {
+            root: root.into(),
+            password: password.to_string(),
+            kdf: Kdf::Blake3,
+            bound: false,
+            keys: Arc::default(),
+        }
+    }
+
+    /// The `puzzles.enc` directory in a data directory.
+    #[must_use]
+    pub fn open(data_dir: &Path, password: &str) -> Self {
+        Self::new(data_dir.join(PUZZLES_ENC_DIR), password)
+    }
+
+    /// Opens the store in a data directory and checks that the password matches it. New files
+    /// use the KDF of the key check record, and if the record authenticates its path, so must
+    /// every other file.
+    ///
+    /// Stores in older formats have no record and stay readable until they are migrated, writing
+    /// new files in a format without paths too. So a store without a record but with files that
+    /// authenticate their path has lost its record, and is refused. An empty store is new, and
+    /// bound from its first file.
+    pub fn unlock(data_dir: &Path, password: &str) -> Result<Self, Error> {
+        let mut store = Self::open(data_dir, password);
+        store.verify_key()?;
+        match fs::read(store.encrypted_path(Path::new(KEY_CHECK))) {
+            Ok(record) => {
+                store.kdf = SaltedKey::kdf_of(&record)?;
+                store.bound = SaltedKey::is_bound(&record)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let files = store.files(Path::new(""));
+                for path in &files {
+                    if SaltedKey::is_bound(&fs::read(store.encrypted_path(path))?)? {
+                        return Err(Error::MissingKeyCheck);
+                    }
+                }
+                store.bound = files.is_empty();
+            }
+            Err(e) => return Err(e.into()),
+        }
+        Ok(store)
+    }
+
+    #[must_use]
+    pub fn kdf(&self) -> Kdf {
+        self.kdf
+    }
+
+    /// Checks the password against the key check record. A store without one is checked by
+    /// decrypting any of its files instead. Checking never writes to the store.
+    pub fn verify_key(&self) -> Result<(), Error> {
+        let record = Path::new(KEY_CHECK);
+        if self.contains(record) {
+            return match self.read_file(record) {
+                Ok(contents) if contents == KEY_CHECK_CONTENTS => Ok(()),
+                Ok(_) | Err(Error::Cipher) => Err(Error::KeyMismatch),
+                Err(e) => Err(e),
+            };
+        }
+
+        let Some(existing) = self.files(Path::new("")).into_iter().next() else {
+            return Ok(());
+        };
+        match self.read_file(&existing) {
+            Ok(_) => Ok(()),
+            Err(Error::Cipher) => Err(Error::KeyMismatch),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn encrypted_path(&self, path: &Path) -> PathBuf {
+        let mut path = self.root.join(path).into_os_string();
+        path.push(".");
+        path.push(ENC_EXTENSION);
+        path.into()
+    }
+
+    /// Re-encrypts every file with a new password and returns the number of files.
+    pub fn rekey(&self, new_password: &str) -> Result<usize, Error> {
+        self.reencrypt(new_password, self.kdf)
+    }
+
+    /// Re-encrypts every file in the current format with the given KDF, upgrading headerless
+    /// files of stores that are not bound yet, and returns the number of files.
+    pub fn migrate(&self, kdf: Kdf) -> Result<usize, Error> {
+        self.reencrypt(&self.password, kdf)
+    }
+
+    /// The new files are written to a staging directory next to the store, and each is decrypted
+    /// again before the store is replaced, so a failure leaves the old files untouched. Plaintext
+    /// is only ever kept in memory.
+    fn reencrypt(&self, new_password: &str, kdf: Kdf) -> Result<usize, Error> {
+        let staging = self.sibling("rekey");
+        let backup = self.sibling("old");
+        for dir in [&staging, &backup] {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+
+        let count = self
+            .reencrypt_into(&staging, new_password, kdf)
+            .inspect_err(|_| {
+                let _ = fs::remove_dir_all(&staging);
+            })?;
+
+        fs::rename(&self.root, &backup)?;
+        if let Err(e) = fs::rename(&staging, &self.root) {
+            fs::rename(&backup, &self.root)?;
+            return Err(e.into());
+        }
+        fs::remove_dir_all(&backup)?;
+        Ok(count)
+    }
+
+    fn reencrypt_into(&self, staging: &Path, new_password: &str, kdf: Kdf) -> Result<usize, Error> {
+        let staged = Self::new(staging, new_password);
+        let key = SaltedKey::with_kdf(new_password, kdf)?;
+        let mut count = 0;
+        for entry in WalkDir::new(&self.root).into_iter().filter_map(Result::ok) {
+            let relative = entry.path().strip_prefix(&self.root).unwrap();
+            let target = staging.join(relative);
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&target)?;
+                continue;
+            }
+
+            if entry
+                .path()
+                .extension()
+                .is_none_or(|ext| ext != ENC_EXTENSION)
+            {
+                fs::copy(entry.path(), &target)?;
+                continue;
+            }
+
+            // The record is written last, with the key shared by every file
+            let path = relative.with_extension("");
+            if path == Path::new(KEY_CHECK) {
+                continue;
+            }
+            // Read like any other file, so that a bound store refuses files in older formats
+            // instead of upgrading them
+            let plaintext = self.read_file(&path)?;
+            staged.write_with(&key, &path, &plaintext)?;
+            let written = fs::read(staged.encrypted_path(&path))?;
+            let extracted = SaltedKey::extract_cached(new_password, &written, &staged.keys)?;
+            if extracted.decrypt(&written, &associated_data(&path))? != plaintext {
+                return Err(Error::Cipher);
+            }
+            count += 1;
+        }
+
+        // Every file is now in the current format, so the store can be bound to it
+        staged.write_with(&key, Path::new(KEY_CHECK), KEY_CHECK_CONTENTS)?;
+        Ok(count)
+    }
+
+    /// The key of new files in a bound store, which is that of the key check record so that they
+    /// share its salt. New stores get their record along with this key.
+    fn store_key(&self) -> Result<SaltedKey, Error> {
+        match fs::read(self.encrypted_path(Path::new(KEY_CHECK))) {
+            Ok(record) => Ok(SaltedKey::extract_cached(
+                &self.password,
+                &record,
+                &self.keys,
+            )?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let key = SaltedKey::with_kdf(&self.password, self.kdf)?;
+                self.write_with(&key, Path::new(KEY_CHECK), KEY_CHECK_CONTENTS)?;
+                Ok(key)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_with(&self, key: &SaltedKey, path: &Path, contents: &[u8]) -> Result<(), Error> {
+        let encrypted = key.encrypt(contents, &associated_data(path))?;
+        let encrypted_path = self.encrypted_path(path);
+        if let Some(parent) = encrypted_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(encrypted_path, encrypted)?;
+        Ok(())
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut path = self.root.clone().into_os_string();
+        path.push(".");
+        path.push(suffix);
+        path.into()
+    }
+}
+
+impl PuzzleStore for EncryptedStore {
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, Error> {
+        let bytes = fs::read(self.encrypted_path(path))?;
+        if self.bound && !SaltedKey::is_bound(&bytes)? {
+            return Err(Error::Cipher);
+        }
+        let bytes = SaltedKey::extract_cached(&self.password, &bytes, &self.keys)?
+            .decrypt(&bytes, &associated_data(path))?;
+        Ok(bytes)
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<(), Error> {
+        let key = if self.bound {
+            self.store_key()?
+        } else {
+            // Stores that are not migrated yet keep the salt of existing files
+            let existing_key = fs::read(self.encrypted_path(path))
+                .ok()
+                .and_then(|existing| {
+                    SaltedKey::extract_cached(&self.password, &existing, &self.keys).ok()
+                });
+            match existing_key {
+                Some(key) => key,
+                None => SaltedKey::unbound_with_kdf(&self.password, self.kdf)?,
+            }
+        };
+        self.write_with(&key, path, contents)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), Error> {
+        Ok(fs::remove_file(self.encrypted_path(path))?)
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.encrypted_path(path).is_file()
+    }
+
+    fn files(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = WalkDir::new(self.root.join(dir))
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|ext| ext == ENC_EXTENSION)
+            })
+            .filter_map(|entry| {
+                let path = entry.path().strip_prefix(&self.root).ok()?;
+                Some(path.with_extension(""))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+/// Files are bound to their path relative to the root, such as `2024/01/part_1/tests/test_01.in`,
+/// so that they fail to decrypt if moved or swapped.
+fn associated_data(path: &Path) -> Vec<u8> {
+    path.iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+        .into_bytes()
+}
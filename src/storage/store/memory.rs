@@ -0,0 +1,63 @@
+use super::PuzzleStore;
+use crate::storage::components::Error;
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// A store that only lives in memory, for tests and for staging files before they are written.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PuzzleStore for MemoryStore {
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, Error> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Error::Io(io::ErrorKind::NotFound.into()))
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<(), Error> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), Error> {
+        self.files
+            .lock()
+            .unwrap()
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| Error::Io(io::ErrorKind::NotFound.into()))
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn files(&self, dir: &Path) -> Vec<PathBuf> {
+        self.files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|path| path.starts_with(dir))
+            .cloned()
+            .collect()
+    }
+}
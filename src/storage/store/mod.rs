@@ -0,0 +1,199 @@
+use crate::{
+    PuzzleId,
+    api::Ledger,
+    storage::components::{
+        self, Description, Error, Puzzle, PuzzleInput, PuzzleMetadata, TestCase,
+    },
+};
+use std::path::{Path, PathBuf};
+
+mod encrypted;
+mod memory;
+mod plaintext;
+
+pub use encrypted::EncryptedStore;
+pub use memory::MemoryStore;
+pub use plaintext::PlaintextStore;
+
+/// Where puzzles, tests, inputs, descriptions and metadata are kept.
+///
+/// Implementations only store files by their path relative to the root of the store, such as
+/// `2024/01/part_1/tests/test_01.in`. The layout of a puzzle on top of those files is shared by
+/// the provided methods.
+pub trait PuzzleStore {
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, Error>;
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<(), Error>;
+
+    fn remove_file(&self, path: &Path) -> Result<(), Error>;
+
+    fn contains(&self, path: &Path) -> bool;
+
+    /// All files below a directory, recursively, relative to the root of the store.
+    fn files(&self, dir: &Path) -> Vec<PathBuf>;
+
+    fn list(&self) -> Vec<PuzzleId> {
+        components::list(self)
+    }
+
+    fn get(&self, id: &PuzzleId) -> Result<Puzzle, Error> {
+        components::get(self, id)
+    }
+
+    fn get_all(&self) -> Result<Vec<Puzzle>, Error> {
+        self.list().into_iter().map(|id| self.get(&id)).collect()
+    }
+
+    fn read_tests(&self, id: &PuzzleId) -> Result<Vec<TestCase>, Error> {
+        components::tests::read_tests(self, id)
+    }
+
+    fn save_test(
+        &self,
+        id: &PuzzleId,
+        test_id: u32,
+        input: &str,
+        expected: &str,
+    ) -> Result<(), Error> {
+        components::tests::save_test(self, id, test_id, input, expected)
+    }
+
+    /// Reads the inputs of a puzzle that have a known expected output.
+    fn read_input(&self, id: &PuzzleId) -> Result<Vec<PuzzleInput>, Error> {
+        components::input::read_input(self, id)
+    }
+
+    /// Reads a single puzzle input, regardless of whether its expected output is known yet.
+    fn read_input_file(&self, id: &PuzzleId, input_id: u32) -> Result<String, Error> {
+        components::input::read_input_file(self, id, input_id)
+    }
+
+    fn save_input(
+        &self,
+        id: &PuzzleId,
+        input_id: u32,
+        input: &str,
+        expected: Option<&str>,
+    ) -> Result<(), Error> {
+        components::input::save_input(self, id, input_id, input, expected)
+    }
+
+    fn get_description(&self, id: &PuzzleId) -> Result<Description, Error> {
+        components::puzzle::get_description(self, id)
+    }
+
+    fn save_description(
+        &self,
+        id: &PuzzleId,
+        description: Option<&str>,
+        styles: Option<&str>,
+    ) -> Result<(), Error> {
+        components::puzzle::save_description(self, id, description, styles)
+    }
+
+    fn read_metadata(&self, id: &PuzzleId) -> Result<PuzzleMetadata, Error> {
+        components::metadata::read_metadata(self, id)
+    }
+
+    fn save_metadata(&self, id: &PuzzleId, metadata: &PuzzleMetadata) -> Result<(), Error> {
+        components::metadata::save_metadata(self, id, metadata)
+    }
+
+    /// Reads the answer ledger of a day, which is empty if nothing has been submitted yet.
+    fn read_ledger(&self, id: &PuzzleId) -> Result<Ledger, Error> {
+        components::ledger::read_ledger(self, id)
+    }
+
+    fn save_ledger(&self, id: &PuzzleId, ledger: &Ledger) -> Result<(), Error> {
+        components::ledger::save_ledger(self, id, ledger)
+    }
+}
+
+/// Copies every file of one store into another, such as when encrypting or decrypting.
+pub fn copy_all(from: &dyn PuzzleStore, to: &dyn PuzzleStore) -> Result<(), Error> {
+    from.files(Path::new(""))
+        .iter()
+        .try_for_each(|path| to.write_file(path, &from.read_file(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_puzzles() {
+        let store = MemoryStore::default();
+        let id = PuzzleId::new(2024, 1, 1);
+        let metadata = PuzzleMetadata {
+            title: "Historian Hysteria".to_string(),
+            ..PuzzleMetadata::default()
+        };
+
+        store.save_metadata(&id, &metadata).unwrap();
+        store.save_test(&id, 2, "3 4\n", "11").unwrap();
+        store.save_test(&id, 1, "1 2\n", "3").unwrap();
+        store.save_input(&id, 1, "5 6\n", None).unwrap();
+        store
+            .save_description(&id, Some("<article></article>"), Some("main {}"))
+            .unwrap();
+
+        assert_eq!(store.list(), vec![id.clone()]);
+        let puzzle = store.get(&id).unwrap();
+        assert_eq!(puzzle.metadata.title, "Historian Hysteria");
+        assert_eq!(
+            puzzle.tests.iter().map(|test| test.id).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert!(puzzle.inputs.is_empty());
+        assert_eq!(store.read_input_file(&id, 1).unwrap(), "5 6\n");
+        assert_eq!(puzzle.description.unwrap().styles, "main {}");
+
+        store.save_input(&id, 1, "5 6\n", Some("30")).unwrap();
+        assert_eq!(store.read_input(&id).unwrap()[0].expected, "30");
+    }
+
+    #[test]
+    fn shares_styles_and_replaces_legacy_copies() {
+        let store = MemoryStore::default();
+        let first = PuzzleId::new(2024, 1, 1);
+        let second = PuzzleId::new(2024, 2, 1);
+
+        store
+            .write_file(Path::new("2024/01/part_1/puzzle/styles.css"), b"old")
+            .unwrap();
+        store
+            .save_description(&first, Some("a"), Some("new"))
+            .unwrap();
+        store
+            .save_description(&second, Some("b"), Some("new"))
+            .unwrap();
+
+        assert!(!store.contains(Path::new("2024/01/part_1/puzzle/styles.css")));
+        assert_eq!(store.files(Path::new("styles")).len(), 1);
+        assert_eq!(store.get_description(&second).unwrap().styles, "new");
+    }
+
+    #[test]
+    fn fails_on_unreadable_files() {
+        let store = MemoryStore::default();
+        let id = PuzzleId::new(2024, 1, 1);
+        store.save_test(&id, 1, "in", "out").unwrap();
+        store
+            .write_file(Path::new("2024/01/part_1/tests/test_02.in"), &[0xff])
+            .unwrap();
+
+        assert!(matches!(store.read_tests(&id), Err(Error::Utf8(_))));
+        assert!(store.read_input(&id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn copies_between_stores() {
+        let from = MemoryStore::default();
+        let to = MemoryStore::default();
+        let id = PuzzleId::new(2015, 25, 2);
+        from.save_test(&id, 1, "in", "out").unwrap();
+
+        copy_all(&from, &to).unwrap();
+        assert_eq!(to.read_tests(&id).unwrap(), from.read_tests(&id).unwrap());
+    }
+}
@@ -0,0 +1,66 @@
+use super::PuzzleStore;
+use crate::storage::{ENC_EXTENSION, PUZZLES_DIR, components::Error};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+/// The decrypted `puzzles` directory, for inspecting and editing files by hand.
+#[derive(Clone, Debug)]
+pub struct PlaintextStore {
+    root: PathBuf,
+}
+
+impl PlaintextStore {
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The `puzzles` directory in a data directory.
+    #[must_use]
+    pub fn open(data_dir: &Path) -> Self {
+        Self::new(data_dir.join(PUZZLES_DIR))
+    }
+}
+
+impl PuzzleStore for PlaintextStore {
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, Error> {
+        Ok(fs::read(self.root.join(path))?)
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<(), Error> {
+        let path = self.root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), Error> {
+        Ok(fs::remove_file(self.root.join(path))?)
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.root.join(path).is_file()
+    }
+
+    fn files(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = WalkDir::new(self.root.join(dir))
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_none_or(|ext| ext != ENC_EXTENSION)
+            })
+            .filter_map(|entry| Some(entry.path().strip_prefix(&self.root).ok()?.to_path_buf()))
+            .collect();
+        files.sort();
+        files
+    }
+}
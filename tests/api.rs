@@ -0,0 +1,442 @@
+use aoc::{
+    PuzzleId,
+    api::{
+        AdventOfCode, ApiError, CachedResponse, Cassette, HttpCache, Ledger, Options, Rejection,
+        SimulatedClock, SubmitOutcome, unlock_time,
+    },
+};
+use mock::{Day, FONT, MockServer, SESSION};
+use std::{
+    fs,
+    net::TcpListener,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+use time::OffsetDateTime;
+
+mod mock;
+
+const CACHE_KEY: &str = "cache-key";
+
+const LEADERBOARD: &str = r#"{
+    "event": "2025",
+    "owner_id": 1,
+    "members": {
+        "1": {
+            "id": 1, "name": "Alice", "stars": 2, "local_score": 4,
+            "completion_day_level": {
+                "1": {"1": {"get_star_ts": 1764565500}, "2": {"get_star_ts": 1764565800}}
+            }
+        }
+    }
+}"#;
+
+fn options(server: &MockServer, state_dir: &TempDir) -> Options {
+    Options {
+        base_url: server.url().to_string(),
+        state_dir: state_dir.path().to_path_buf(),
+        min_interval: Duration::ZERO,
+        cache_key: Some(CACHE_KEY.to_string()),
+        ..Options::default()
+    }
+}
+
+/// Every entry of the HTTP cache, as it is stored on disk.
+fn cache_entries(state_dir: &TempDir) -> Vec<String> {
+    fs::read_dir(state_dir.path().join("cache"))
+        .unwrap()
+        .map(|entry| {
+            String::from_utf8_lossy(&fs::read(entry.unwrap().path()).unwrap()).into_owned()
+        })
+        .collect()
+}
+
+fn setup() -> (MockServer, AdventOfCode, TempDir) {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let state_dir = TempDir::new().unwrap();
+    let api = AdventOfCode::new(SESSION, &options(&server, &state_dir)).unwrap();
+    (server, api, state_dir)
+}
+
+#[test]
+fn downloads_puzzle_and_input() {
+    let (server, api, _state_dir) = setup();
+    let id = PuzzleId::new(2025, 1, 1);
+
+    let prompt = api.get_puzzle(&id).unwrap();
+    assert!(
+        prompt
+            .description
+            .contains("--- Day 1: Secret Entrance ---")
+    );
+    assert!(prompt.styles.contains("background: #0f0f23"));
+    assert_eq!(prompt.title.as_deref(), Some("Secret Entrance"));
+    assert_eq!(prompt.url, format!("{}/2025/day/1", server.url()));
+
+    let input = api.get_input(&id).unwrap();
+    assert_eq!(input, Day::example().input);
+
+    assert_eq!(
+        server.requests(),
+        [
+            "GET /2025/day/1",
+            "GET /static/style.css",
+            "GET /2025/day/1/input"
+        ]
+    );
+    // Stylesheets do not need the session
+    assert_eq!(
+        server.authenticated_requests(),
+        ["GET /2025/day/1", "GET /2025/day/1/input"]
+    );
+}
+
+#[test]
+fn fetches_assets_without_the_session() {
+    let (server, api, _state_dir) = setup();
+
+    let (content_type, bytes) = api
+        .get_asset(&format!("{}/static/style.css", server.url()))
+        .unwrap();
+    assert_eq!(content_type, "text/css");
+    assert!(String::from_utf8(bytes).unwrap().contains("#0f0f23"));
+    assert!(server.authenticated_requests().is_empty());
+
+    let foreign = api.get_asset("https://fonts.example/font.woff2");
+    assert!(matches!(foreign, Err(ApiError::ForeignHost(_))));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn part_two_requires_part_one() {
+    let (server, api, _state_dir) = setup();
+
+    assert!(api.get_puzzle(&PuzzleId::new(2025, 1, 2)).is_err());
+
+    server.set_solved(2025, 1, 1);
+    let prompt = api.get_puzzle(&PuzzleId::new(2025, 1, 2)).unwrap();
+    assert!(prompt.description.contains("--- Part Two ---"));
+}
+
+#[test]
+fn refuses_locked_days() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let state_dir = TempDir::new().unwrap();
+    let clock = SimulatedClock::new(unlock_time(2025, 1) - time::Duration::SECOND);
+    let options = Options {
+        clock: Arc::new(clock),
+        ..options(&server, &state_dir)
+    };
+    let api = AdventOfCode::new(SESSION, &options).unwrap();
+    let id = PuzzleId::new(2025, 1, 1);
+
+    assert!(!api.is_day_unlocked(&id));
+    assert!(matches!(api.get_puzzle(&id), Err(ApiError::DayLocked)));
+    assert!(matches!(api.get_input(&id), Err(ApiError::DayLocked)));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn reports_error_statuses() {
+    let (server, api, state_dir) = setup();
+    server.lock_day(2025, 2);
+
+    let missing = api.get_puzzle(&PuzzleId::new(2025, 3, 1));
+    assert!(matches!(missing, Err(ApiError::NotFound)));
+
+    let locked = api.get_input(&PuzzleId::new(2025, 2, 1));
+    assert!(matches!(locked, Err(ApiError::TooEarly)));
+
+    let logged_out = AdventOfCode::new("expired-session", &options(&server, &state_dir))
+        .unwrap()
+        .get_input(&PuzzleId::new(2025, 1, 1));
+    assert!(matches!(logged_out, Err(ApiError::Unauthorized)));
+    assert_eq!(
+        logged_out.unwrap_err().to_string(),
+        "the session is invalid or has expired, update AOC_SESSION"
+    );
+}
+
+#[test]
+fn recovers_answers() {
+    let (server, api, _state_dir) = setup();
+    let id = PuzzleId::new(2025, 1, 1);
+
+    assert!(api.get_answers(&id).unwrap().is_empty());
+
+    server.set_solved(2025, 1, 1);
+    assert_eq!(api.get_answers(&id).unwrap(), ["3"]);
+
+    server.set_solved(2025, 1, 2);
+    assert_eq!(api.get_answers(&id).unwrap(), ["3", "6"]);
+}
+
+#[test]
+fn submits_answers() {
+    let (_server, api, _state_dir) = setup();
+    let mut ledger = Ledger::default();
+    let part_1 = PuzzleId::new(2025, 1, 1);
+    let part_2 = PuzzleId::new(2025, 1, 2);
+
+    let submit = |id: &PuzzleId, answer: &str, ledger: &mut Ledger| {
+        api.submit(id, answer.to_string(), ledger).unwrap()
+    };
+
+    assert_eq!(submit(&part_2, "6", &mut ledger), SubmitOutcome::WrongLevel);
+    assert_eq!(submit(&part_1, "1", &mut ledger), SubmitOutcome::TooLow);
+    assert_eq!(submit(&part_1, "9", &mut ledger), SubmitOutcome::TooHigh);
+    assert_eq!(submit(&part_1, "3", &mut ledger), SubmitOutcome::Correct);
+    assert_eq!(
+        submit(&part_1, "3", &mut ledger),
+        SubmitOutcome::AlreadyCompleted
+    );
+    assert_eq!(submit(&part_2, "6", &mut ledger), SubmitOutcome::Correct);
+    assert_eq!(ledger.attempts.len(), 6);
+}
+
+#[test]
+fn rate_limited_submission() {
+    let (server, api, _state_dir) = setup();
+    let mut ledger = Ledger::default();
+    let id = PuzzleId::new(2025, 1, 1);
+    server.rate_limit(Some(65));
+
+    let outcome = api.submit(&id, "3".to_string(), &mut ledger).unwrap();
+    assert_eq!(outcome, SubmitOutcome::RateLimited(Duration::from_secs(65)));
+
+    let rejected = api.submit(&id, "3".to_string(), &mut ledger);
+    assert!(matches!(
+        rejected,
+        Err(ApiError::Rejected(Rejection::LockedOut(_)))
+    ));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn ledger_rejects_without_contacting_server() {
+    let (server, api, _state_dir) = setup();
+    let mut ledger = Ledger::default();
+    let id = PuzzleId::new(2025, 1, 1);
+
+    api.submit(&id, "9".to_string(), &mut ledger).unwrap();
+    let rejected = api.submit(&id, "12".to_string(), &mut ledger);
+
+    assert!(matches!(
+        rejected,
+        Err(ApiError::Rejected(Rejection::AboveUpperBound(9)))
+    ));
+    assert_eq!(server.requests(), ["POST /2025/day/1/answer"]);
+}
+
+#[test]
+fn retries_server_errors() {
+    let (server, api, _state_dir) = setup();
+    server.fail_next(1);
+
+    let input = api.get_input(&PuzzleId::new(2025, 1, 1)).unwrap();
+    assert_eq!(input, Day::example().input);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn never_retries_submissions() {
+    let (server, api, _state_dir) = setup();
+    let mut ledger = Ledger::default();
+    server.fail_next(1);
+
+    let result = api.submit(&PuzzleId::new(2025, 1, 1), "3".to_string(), &mut ledger);
+    assert!(matches!(result, Err(ApiError::ServerError(_))));
+    assert_eq!(server.requests(), ["POST /2025/day/1/answer"]);
+}
+
+#[test]
+fn throttles_and_audits_requests() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let state_dir = TempDir::new().unwrap();
+    let options = Options {
+        min_interval: Duration::from_millis(100),
+        ..options(&server, &state_dir)
+    };
+    let id = PuzzleId::new(2025, 1, 1);
+
+    AdventOfCode::new(SESSION, &options)
+        .unwrap()
+        .get_input(&id)
+        .unwrap();
+    let start = Instant::now();
+    AdventOfCode::new(SESSION, &options)
+        .unwrap()
+        .get_input(&id)
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    let log = fs::read_to_string(state_dir.path().join("requests.log")).unwrap();
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with(&format!("GET {}/2025/day/1/input 200", server.url())));
+}
+
+#[test]
+fn revalidates_cached_stylesheets() {
+    let (server, api, state_dir) = setup();
+    server.set_solved(2025, 1, 1);
+
+    let part_1 = api.get_puzzle(&PuzzleId::new(2025, 1, 1)).unwrap();
+    let part_2 = api.get_puzzle(&PuzzleId::new(2025, 1, 2)).unwrap();
+
+    assert_eq!(part_1.styles, part_2.styles);
+    assert!(part_2.styles.contains("background: #0f0f23"));
+    let entries = cache_entries(&state_dir);
+    assert_eq!(entries.len(), 1);
+    assert!(!entries[0].contains("#0f0f23"));
+}
+
+#[test]
+fn caches_leaderboards() {
+    let (server, api, state_dir) = setup();
+    server.add_leaderboard(2025, 1234, LEADERBOARD);
+
+    let first = api.get_leaderboard(2025, 1234).unwrap();
+    let second = api.get_leaderboard(2025, 1234).unwrap();
+
+    assert_eq!(first.standings()[0].display_name(), "Alice");
+    assert_eq!(first.fetched_at, second.fetched_at);
+    assert_eq!(server.requests().len(), 1);
+
+    // Member names are private, so they must not be readable from the cache
+    let entries = cache_entries(&state_dir);
+    assert_eq!(entries.len(), 1);
+    assert!(!entries[0].contains("Alice"));
+}
+
+#[test]
+fn skips_the_disk_cache_without_a_key() {
+    let (server, _api, state_dir) = setup();
+    server.add_leaderboard(2025, 1234, LEADERBOARD);
+    let options = Options {
+        cache_key: None,
+        ..options(&server, &state_dir)
+    };
+    let api = AdventOfCode::new(SESSION, &options).unwrap();
+
+    api.get_leaderboard(2025, 1234).unwrap();
+    assert!(!state_dir.path().join("cache").exists());
+
+    // Only the time of the fetch is kept, which still holds back the next one
+    let second = AdventOfCode::new(SESSION, &options).unwrap();
+    assert!(matches!(
+        second.get_leaderboard(2025, 1234),
+        Err(ApiError::TooSoon(_))
+    ));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn uses_stale_leaderboard_when_offline() {
+    let unreachable = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let state_dir = TempDir::new().unwrap();
+    let fetched_at = OffsetDateTime::now_utc() - Duration::from_secs(60 * 60);
+
+    HttpCache::new(&state_dir.path().join("cache"), CACHE_KEY)
+        .put(&CachedResponse {
+            url: format!("{unreachable}/2025/leaderboard/private/view/1234.json"),
+            etag: None,
+            last_modified: None,
+            fetched_at,
+            body: LEADERBOARD.to_string(),
+        })
+        .unwrap();
+
+    let options = Options {
+        base_url: unreachable,
+        state_dir: state_dir.path().to_path_buf(),
+        min_interval: Duration::ZERO,
+        cache_key: Some(CACHE_KEY.to_string()),
+        ..Options::default()
+    };
+    let api = AdventOfCode::new(SESSION, &options).unwrap();
+    let leaderboard = api.get_leaderboard(2025, 1234).unwrap();
+
+    assert_eq!(leaderboard.fetched_at, Some(fetched_at));
+    assert_eq!(leaderboard.members.len(), 1);
+}
+
+#[test]
+fn records_and_replays_cassettes() {
+    let (server, _api, state_dir) = setup();
+    server.add_leaderboard(2025, 1234, LEADERBOARD);
+    let path = state_dir.path().join("cassette.toml.enc");
+    let id = PuzzleId::new(2025, 1, 1);
+
+    // A fresh cached copy is not used, so that the request is recorded
+    let leaderboard_url = format!("{}/2025/leaderboard/private/view/1234.json", server.url());
+    HttpCache::new(&state_dir.path().join("cache"), CACHE_KEY)
+        .put(&CachedResponse {
+            url: leaderboard_url.clone(),
+            etag: None,
+            last_modified: None,
+            fetched_at: OffsetDateTime::now_utc(),
+            body: LEADERBOARD.to_string(),
+        })
+        .unwrap();
+
+    let recording = Options {
+        cassette: Some(Arc::new(Cassette::record(&path, "cassette-key"))),
+        ..options(&server, &state_dir)
+    };
+    let api = AdventOfCode::new(SESSION, &recording).unwrap();
+    let prompt = api.get_puzzle(&id).unwrap();
+    let outcome = api.submit(&id, "9".to_string(), &mut Ledger::default());
+    let leaderboard = api.get_leaderboard(2025, 1234).unwrap();
+    let font_url = format!("{}/static/font.woff2", server.url());
+    assert_eq!(api.get_asset(&font_url).unwrap().1, FONT);
+
+    // The cassette is written once, when recording is done
+    assert!(!path.exists());
+    let cassette = Arc::clone(recording.cassette.as_ref().unwrap());
+    assert!(
+        cassette
+            .requests()
+            .contains(&("GET".to_string(), leaderboard_url))
+    );
+    drop((api, recording, cassette));
+
+    let encrypted = fs::read(&path).unwrap();
+    assert!(!String::from_utf8_lossy(&encrypted).contains("Secret Entrance"));
+    assert!(Cassette::replay(&path, "wrong-key").is_err());
+
+    // Replay with an empty HTTP cache, so that every page has to come from the cassette
+    let requests = server.requests();
+    let replay_dir = TempDir::new().unwrap();
+    let replaying = Options {
+        cassette: Some(Arc::new(Cassette::replay(&path, "cassette-key").unwrap())),
+        ..options(&server, &replay_dir)
+    };
+    let api = AdventOfCode::new(SESSION, &replaying).unwrap();
+
+    let replayed = api.get_puzzle(&id).unwrap();
+    assert_eq!(replayed.description, prompt.description);
+    assert_eq!(replayed.styles, prompt.styles);
+    assert_eq!(
+        api.submit(&id, "9".to_string(), &mut Ledger::default())
+            .unwrap(),
+        outcome.unwrap()
+    );
+    assert_eq!(
+        api.get_leaderboard(2025, 1234).unwrap().members.len(),
+        leaderboard.members.len()
+    );
+    assert_eq!(
+        api.get_asset(&font_url).unwrap(),
+        ("font/woff2".to_string(), FONT.to_vec())
+    );
+    assert!(matches!(api.get_input(&id), Err(ApiError::Cassette(_))));
+    assert_eq!(server.requests(), requests);
+}
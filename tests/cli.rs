@@ -0,0 +1,619 @@
+use aoc::storage::{EncryptedStore, Kdf, PuzzleStore, SaltedKey};
+use mock::{Day, MockServer, SESSION};
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+use tempfile::TempDir;
+
+mod mock;
+
+const KEY: &str = "mock-key";
+
+fn aoc(server: &MockServer, dir: &Path, args: &[&str]) -> String {
+    aoc_with(server, dir, args, &[])
+}
+
+/// Runs the CLI with extra environment variables, which override the defaults.
+fn aoc_with(server: &MockServer, dir: &Path, args: &[&str], vars: &[(&str, &str)]) -> String {
+    let Output { stdout, .. } = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(args)
+        .current_dir(dir)
+        .env("AOC_KEY", KEY)
+        .env("AOC_SESSION", SESSION)
+        .env("AOC_DATA_DIR", dir)
+        .env("AOC_BASE_URL", server.url())
+        .env("AOC_MIN_INTERVAL_MS", "0")
+        .envs(vars.iter().copied())
+        .output()
+        .unwrap();
+    String::from_utf8(stdout).unwrap()
+}
+
+fn store(dir: &Path) -> EncryptedStore {
+    EncryptedStore::new(dir.join("puzzles.enc"), KEY)
+}
+
+fn encrypt(dir: &Path, path: &str, contents: &str) {
+    store(dir)
+        .write_file(Path::new(path), contents.as_bytes())
+        .unwrap();
+}
+
+/// Rewrites a store in the format used before file headers, like the store in this repository,
+/// which also had no key check record.
+fn make_headerless(dir: &Path) {
+    let _ = fs::remove_file(dir.join("puzzles.enc/key.check.enc"));
+    let store = store(dir);
+    for path in store.files(Path::new("")) {
+        let contents = store.read_file(&path).unwrap();
+        let legacy = SaltedKey::unbound_with_kdf(KEY, Kdf::Blake3)
+            .unwrap()
+            .encrypt(&contents, &[])
+            .unwrap();
+        let path = format!("puzzles.enc/{}.enc", path.display());
+        fs::write(dir.join(path), &legacy[6..]).unwrap();
+    }
+}
+
+fn decrypt(dir: &Path, path: &str) -> String {
+    String::from_utf8(store(dir).read_file(Path::new(path)).unwrap()).unwrap()
+}
+
+#[test]
+fn download_flow() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    assert!(output.contains("Downloaded puzzle and input for 2025 day 01 part 1"));
+
+    let description = decrypt(dir.path(), "2025/01/part_1/puzzle/description.html");
+    assert!(description.contains("--- Day 1: Secret Entrance ---"));
+    let input = decrypt(dir.path(), "2025/01/part_1/inputs/puzzle_01.in");
+    assert_eq!(input, Day::example().input);
+
+    let metadata = decrypt(dir.path(), "2025/01/meta.toml");
+    assert!(metadata.contains("title = \"Secret Entrance\""));
+    assert!(metadata.contains("downloaded_at"));
+    assert!(metadata.contains(&format!("source = \"{}/2025/day/1\"", server.url())));
+
+    // Part 2 is unsolved, which is known now, so nothing is requested again
+    let requests = server.requests().len();
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    assert!(output.contains("Puzzle 2025 day 01 part 1 already downloaded"));
+    assert_eq!(server.requests().len(), requests);
+
+    // Asking for part 2 means part 1 has been solved since
+    server.set_solved(2025, 1, 1);
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "2"]);
+    assert!(output.contains("Recovered answer for 2025 day 01 part 1"));
+    assert_eq!(
+        decrypt(dir.path(), "2025/01/part_1/inputs/puzzle_01.out"),
+        "3"
+    );
+}
+
+#[test]
+fn keeps_unreadable_metadata() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+
+    let description = dir
+        .path()
+        .join("puzzles.enc/2025/01/part_1/puzzle/description.html.enc");
+    fs::remove_file(description).unwrap();
+    encrypt(dir.path(), "2025/01/meta.toml", "title = ");
+
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    assert!(output.contains("Error: Failed to read metadata: invalid file contents"));
+    assert_eq!(decrypt(dir.path(), "2025/01/meta.toml"), "title = ");
+}
+
+#[test]
+fn recovers_answers_on_download() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    server.set_solved(2025, 1, 2);
+    let dir = TempDir::new().unwrap();
+
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    assert!(output.contains("Downloaded puzzle and input for 2025 day 01 part 1"));
+    assert!(output.contains("Recovered answer for 2025 day 01 part 1"));
+    assert!(output.contains("Recovered answer for 2025 day 01 part 2"));
+
+    assert_eq!(
+        decrypt(dir.path(), "2025/01/part_1/inputs/puzzle_01.out"),
+        "3"
+    );
+    assert_eq!(
+        decrypt(dir.path(), "2025/01/part_2/inputs/puzzle_01.out"),
+        "6"
+    );
+    assert_eq!(
+        decrypt(dir.path(), "2025/01/part_2/inputs/puzzle_01.in"),
+        Day::example().input
+    );
+
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    assert!(output.contains("already downloaded"));
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "2"]);
+    assert!(output.contains("Downloaded puzzle for 2025 day 01 part 2"));
+}
+
+#[test]
+fn checks_session() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+
+    let output = aoc(&server, dir.path(), &["whoami"]);
+    assert!(output.contains("Logged in as Mock User"));
+
+    let expired = [("AOC_SESSION", "expired-session")];
+    let output = aoc_with(&server, dir.path(), &["whoami"], &expired);
+    assert!(output.contains("AOC_SESSION is invalid or has expired"));
+
+    let args = ["download", "2025", "1", "1"];
+    let output = aoc_with(&server, dir.path(), &args, &expired);
+    assert!(output.contains("AOC_SESSION is invalid or has expired"));
+    assert!(!dir.path().join("puzzles.enc").exists());
+
+    let args = ["submit", "2025", "1", "1", "3"];
+    let output = aoc_with(&server, dir.path(), &args, &expired);
+    assert!(output.contains("AOC_SESSION is invalid or has expired"));
+    assert!(
+        !server
+            .requests()
+            .iter()
+            .any(|request| request.contains("/day/"))
+    );
+}
+
+#[test]
+fn batch_download() {
+    let server = MockServer::start();
+    server.add_day(2024, 1, Day::example());
+    server.add_day(2025, 1, Day::example());
+    server.add_day(2025, 2, Day::example());
+    server.set_solved(2025, 1, 2);
+    let dir = TempDir::new().unwrap();
+
+    let output = aoc(&server, dir.path(), &["download", "2025"]);
+    assert!(output.contains("Downloaded puzzle and input for 2025 day 01 part 1"));
+    assert!(output.contains("Downloaded puzzle for 2025 day 01 part 2"));
+    assert!(output.contains("Downloaded puzzle and input for 2025 day 02 part 1"));
+    assert!(output.contains("Downloaded 3 parts, 0 already cached, 0 failed"));
+    assert_eq!(
+        decrypt(dir.path(), "2025/01/part_2/inputs/puzzle_01.out"),
+        "6"
+    );
+
+    let output = aoc(&server, dir.path(), &["download", "--all"]);
+    assert!(output.contains("Downloaded puzzle and input for 2024 day 01 part 1"));
+    assert!(output.contains("Downloaded 1 parts, 3 already cached, 0 failed"));
+}
+
+#[test]
+fn waits_for_unlock() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+
+    // Three seconds before the first puzzle of 2025 unlocks, with a simulated clock
+    let now = [("AOC_NOW", "2025-12-01T04:59:57Z")];
+    let output = aoc_with(&server, dir.path(), &["wait", "--test"], &now);
+    assert!(output.contains("Waiting for 2025 day 01"));
+    assert!(output.contains("Unlocks in 00:00:03"));
+    assert!(output.contains("Downloaded puzzle and input for 2025 day 01 part 1"));
+    assert!(output.contains("Answer: 3"));
+    assert_eq!(
+        decrypt(dir.path(), "2025/01/part_1/inputs/puzzle_01.in"),
+        Day::example().input
+    );
+}
+
+#[test]
+fn submit_flow() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+
+    let output = aoc(&server, dir.path(), &["submit", "2025", "1", "1", "2"]);
+    assert!(output.contains("too low"));
+
+    let output = aoc(&server, dir.path(), &["submit", "2025", "1", "1", "2"]);
+    assert!(output.contains("already rejected"));
+
+    let output = aoc(&server, dir.path(), &["submit", "2025", "1", "1"]);
+    assert!(output.contains("Submitting 3"));
+    assert!(output.contains("right answer"));
+
+    let expected = decrypt(dir.path(), "2025/01/part_1/inputs/puzzle_01.out");
+    assert_eq!(expected, "3");
+
+    let ledger = decrypt(dir.path(), "2025/01/ledger.toml");
+    assert!(ledger.contains("too_low"));
+    assert!(ledger.contains("correct"));
+}
+
+#[test]
+fn stylesheets_are_stored_once() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    server.set_solved(2025, 1, 1);
+    let dir = TempDir::new().unwrap();
+
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    aoc(&server, dir.path(), &["download", "2025", "1", "2"]);
+
+    let styles: Vec<_> = fs::read_dir(dir.path().join("puzzles.enc/styles"))
+        .unwrap()
+        .collect();
+    assert_eq!(styles.len(), 1);
+
+    let part_1 = decrypt(dir.path(), "2025/01/part_1/puzzle/styles.ref");
+    let part_2 = decrypt(dir.path(), "2025/01/part_2/puzzle/styles.ref");
+    assert_eq!(part_1, part_2);
+    assert!(
+        dir.path()
+            .join(format!("puzzles.enc/styles/{part_1}.css.enc"))
+            .exists()
+    );
+}
+
+#[test]
+fn leaderboard_standings() {
+    let server = MockServer::start();
+    server.add_leaderboard(
+        2025,
+        1234,
+        r#"{"event": "2025", "owner_id": 1, "members": {"1": {
+            "id": 1, "name": "Alice", "stars": 2, "local_score": 4,
+            "completion_day_level": {
+                "1": {"1": {"get_star_ts": 1764565500}, "2": {"get_star_ts": 1764565800}}
+            }
+        }}}"#,
+    );
+    let dir = TempDir::new().unwrap();
+
+    let output = aoc(
+        &server,
+        dir.path(),
+        &["leaderboard", "1234", "--year", "2025"],
+    );
+    assert!(output.contains("Private leaderboard 1234 (2025)"));
+    assert!(output.contains("  1      4      2  Alice"));
+    assert!(output.contains("5m 0s"));
+}
+
+#[test]
+fn sync_flow() {
+    let server = MockServer::start();
+    for day in 1..=3 {
+        server.add_day(2025, day, Day::example());
+    }
+    server.set_solved(2025, 1, 2);
+    server.set_solved(2025, 3, 1);
+    let dir = TempDir::new().unwrap();
+
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    aoc(&server, dir.path(), &["download", "2025", "2", "1"]);
+    encrypt(
+        dir.path(),
+        "2025/01/meta.toml",
+        "title = \"Secret Entrance\"\n",
+    );
+
+    let output = aoc(&server, dir.path(), &["sync"]);
+    assert!(output.contains("Updated stars of 1 days"));
+    assert!(output.contains("Stars without local data:\n  2025 day 03 (1 stars)"));
+    assert!(output.contains("Local data without stars:\n  2025 day 02"));
+
+    let metadata = decrypt(dir.path(), "2025/01/meta.toml");
+    assert!(metadata.contains("stars = 2"));
+}
+
+#[test]
+fn finds_data_dir() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+    let nested = dir.path().join("src/years");
+    fs::create_dir_all(dir.path().join("puzzles.enc")).unwrap();
+    fs::create_dir_all(&nested).unwrap();
+    let discover = [("AOC_DATA_DIR", "")];
+
+    aoc_with(&server, &nested, &["download", "2025", "1", "1"], &discover);
+    assert!(dir.path().join("puzzles.enc/2025/01").exists());
+    assert!(dir.path().join(".aoc").exists());
+    assert!(!nested.join(".aoc").exists());
+
+    let output = aoc_with(&server, &nested, &["show", "2025", "1", "1"], &discover);
+    assert!(output.contains("--- Day 1: Secret Entrance ---"));
+
+    let elsewhere = TempDir::new().unwrap();
+    let output = aoc_with(
+        &server,
+        elsewhere.path(),
+        &["show", "2025", "1", "1"],
+        &discover,
+    );
+    assert!(output.contains("Error: No puzzles.enc found"));
+
+    let data_dir = dir.path().to_str().unwrap();
+    let output = aoc_with(
+        &server,
+        elsewhere.path(),
+        &["show", "2025", "1", "1", "--data-dir", data_dir],
+        &discover,
+    );
+    assert!(output.contains("--- Day 1: Secret Entrance ---"));
+}
+
+#[test]
+fn rekeys_store() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    let description = decrypt(dir.path(), "2025/01/part_1/puzzle/description.html");
+
+    let output = aoc_with(
+        &server,
+        dir.path(),
+        &["rekey"],
+        &[("AOC_KEY", "wrong-key"), ("AOC_NEW_KEY", "new-key")],
+    );
+    assert!(output.contains("Error: AOC_KEY does not match this store"));
+    assert_eq!(
+        decrypt(dir.path(), "2025/01/part_1/puzzle/description.html"),
+        description
+    );
+    assert!(!dir.path().join("puzzles.enc.rekey").exists());
+
+    let output = aoc_with(
+        &server,
+        dir.path(),
+        &["rekey"],
+        &[("AOC_NEW_KEY", "new-key")],
+    );
+    assert!(output.contains("Re-encrypted"));
+    let store = EncryptedStore::new(dir.path().join("puzzles.enc"), "new-key");
+    let path = Path::new("2025/01/part_1/puzzle/description.html");
+    assert_eq!(store.read_file(path).unwrap(), description.as_bytes());
+    assert!(self::store(dir.path()).read_file(path).is_err());
+    assert!(!dir.path().join("puzzles.enc.old").exists());
+}
+
+#[test]
+fn rejects_wrong_key() {
+    let server = MockServer::start();
+    let dir = TempDir::new().unwrap();
+    encrypt(
+        dir.path(),
+        "2025/01/meta.toml",
+        "title = \"Secret Entrance\"\n",
+    );
+
+    // Stores without a key check record are checked against their files, and are left as they are
+    let output = aoc(&server, dir.path(), &["solve", "2025", "1", "1"]);
+    assert!(!output.contains("AOC_KEY"));
+    assert!(!dir.path().join("puzzles.enc/key.check.enc").exists());
+
+    let output = aoc_with(
+        &server,
+        dir.path(),
+        &["solve", "2025", "1", "1"],
+        &[("AOC_KEY", "wrong-key")],
+    );
+    assert!(output.contains("Error: AOC_KEY does not match this store"));
+}
+
+#[test]
+fn migrates_store() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    server.add_day(2025, 2, Day::example());
+    let dir = TempDir::new().unwrap();
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    make_headerless(dir.path());
+    let path = dir
+        .path()
+        .join("puzzles.enc/2025/01/part_1/puzzle/description.html.enc");
+    let legacy = fs::read(&path).unwrap();
+
+    let record = dir.path().join("puzzles.enc/key.check.enc");
+    let output = aoc(&server, dir.path(), &["migrate", "--kdf", "argon2id"]);
+    assert!(output.contains("to argon2id"));
+    let migrated = fs::read(&path).unwrap();
+    assert!(fs::read(&record).unwrap().starts_with(b"AOCE\x03\x01"));
+    assert!(migrated.starts_with(b"AOCE\x03\x01"));
+    // Every file shares the salt of the record, so the key is derived once
+    assert_eq!(fs::read(&record).unwrap()[18..50], migrated[18..50]);
+    let output = aoc(&server, dir.path(), &["show", "2025", "1", "1"]);
+    assert!(output.contains("--- Day 1: Secret Entrance ---"));
+
+    // Once migrated, files in older formats are rejected
+    fs::write(&path, &legacy).unwrap();
+    let output = aoc(&server, dir.path(), &["show", "2025", "1", "1"]);
+    assert!(output.contains("Error: Failed to read the description of part 1: decryption failed"));
+    let output = aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    assert!(output.contains("Error: Failed to read stored puzzle 2025 day 01 part 1"));
+    assert_eq!(fs::read(&path).unwrap(), legacy);
+
+    // Nor are they upgraded by rewriting the store
+    let output = aoc_with(
+        &server,
+        dir.path(),
+        &["rekey"],
+        &[("AOC_NEW_KEY", "new-key")],
+    );
+    assert!(output.contains("Error: Failed to re-encrypt, no files were changed"));
+    let output = aoc(&server, dir.path(), &["migrate"]);
+    assert!(output.contains("Error: Failed to migrate, no files were changed"));
+    assert_eq!(fs::read(&path).unwrap(), legacy);
+
+    // New files keep the KDF of the store
+    aoc(&server, dir.path(), &["download", "2025", "2", "1"]);
+    let path = dir
+        .path()
+        .join("puzzles.enc/2025/02/part_1/puzzle/description.html.enc");
+    let new = fs::read(path).unwrap();
+    assert!(new.starts_with(b"AOCE\x03\x01"));
+    assert_eq!(fs::read(&record).unwrap()[18..50], new[18..50]);
+}
+
+#[test]
+fn reads_headerless_stores() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    server.add_day(2025, 2, Day::example());
+    let dir = TempDir::new().unwrap();
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    encrypt(dir.path(), "2025/01/part_1/inputs/puzzle_01.out", "3");
+    make_headerless(dir.path());
+
+    let path = dir
+        .path()
+        .join("puzzles.enc/2025/01/part_1/puzzle/description.html.enc");
+    let legacy = fs::read(&path).unwrap();
+    assert!(!legacy.starts_with(b"AOCE"));
+
+    let output = aoc(&server, dir.path(), &["show", "2025", "1", "1"]);
+    assert!(output.contains("--- Day 1: Secret Entrance ---"));
+    let output = aoc(&server, dir.path(), &["solve", "2025", "1", "1"]);
+    assert!(output.contains("Expected: 3"));
+    assert!(!dir.path().join("puzzles.enc/key.check.enc").exists());
+    assert_eq!(fs::read(&path).unwrap(), legacy);
+
+    // New files do not authenticate their path either, until the store is migrated
+    aoc(&server, dir.path(), &["download", "2025", "2", "1"]);
+    let new = dir
+        .path()
+        .join("puzzles.enc/2025/02/part_1/puzzle/description.html.enc");
+    assert!(fs::read(new).unwrap().starts_with(b"AOCE\x01"));
+    let output = aoc(&server, dir.path(), &["show", "2025", "1", "1"]);
+    assert!(output.contains("--- Day 1: Secret Entrance ---"));
+
+    let output = aoc_with(
+        &server,
+        dir.path(),
+        &["show", "2025", "1", "1"],
+        &[("AOC_KEY", "wrong-key")],
+    );
+    assert!(output.contains("Error: AOC_KEY does not match this store"));
+}
+
+#[test]
+fn refuses_stores_without_their_record() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    let record = dir.path().join("puzzles.enc/key.check.enc");
+    assert!(fs::read(&record).unwrap().starts_with(b"AOCE\x03"));
+
+    // Without the record, a file in an older format could be put in place of another
+    fs::remove_file(&record).unwrap();
+    let legacy = SaltedKey::unbound_with_kdf(KEY, Kdf::Blake3)
+        .unwrap()
+        .encrypt(b"999", &[])
+        .unwrap();
+    let path = dir
+        .path()
+        .join("puzzles.enc/2025/01/part_1/inputs/puzzle_01.out.enc");
+    fs::write(path, &legacy[6..]).unwrap();
+
+    let output = aoc(&server, dir.path(), &["solve", "2025", "1", "1"]);
+    assert!(output.contains("Error: the key check record of this store is missing"));
+}
+
+#[test]
+fn binds_files_to_paths() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let dir = TempDir::new().unwrap();
+    aoc(&server, dir.path(), &["download", "2025", "1", "1"]);
+    let description = decrypt(dir.path(), "2025/01/part_1/puzzle/description.html");
+
+    let day = dir.path().join("puzzles.enc/2025/01");
+    fs::create_dir_all(day.join("part_2/puzzle")).unwrap();
+    fs::copy(
+        day.join("part_1/puzzle/description.html.enc"),
+        day.join("part_2/puzzle/description.html.enc"),
+    )
+    .unwrap();
+
+    assert!(!description.is_empty());
+    assert!(
+        store(dir.path())
+            .read_file(Path::new("2025/01/part_2/puzzle/description.html"))
+            .is_err()
+    );
+}
+
+#[test]
+fn bundles_puzzle_data() {
+    let server = MockServer::start();
+    server.add_day(2025, 1, Day::example());
+    let (from, to) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    aoc(&server, from.path(), &["download", "2025", "1", "1"]);
+    let bundle = from.path().join("2025.bundle");
+    let bundle = bundle.to_str().unwrap();
+    let vars = [("AOC_BUNDLE_KEY", "bundle-key")];
+
+    let output = aoc_with(
+        &server,
+        from.path(),
+        &["bundle", "export", bundle, "--year", "2025"],
+        &vars,
+    );
+    assert!(output.contains("Exported"));
+
+    let output = aoc_with(
+        &server,
+        to.path(),
+        &["bundle", "import", bundle],
+        &[("AOC_BUNDLE_KEY", "wrong-key")],
+    );
+    assert!(output.contains("Error: Failed to open bundle"));
+
+    let output = aoc_with(&server, to.path(), &["bundle", "import", bundle], &vars);
+    assert!(output.contains("0 already up to date, 0 conflicts"));
+    let path = "2025/01/part_1/puzzle/description.html";
+    assert_eq!(decrypt(to.path(), path), decrypt(from.path(), path));
+
+    encrypt(to.path(), path, "<article>Edited</article>");
+    let output = aoc_with(&server, to.path(), &["bundle", "import", bundle], &vars);
+    assert!(output.contains(&format!("Conflict: {path} differs")));
+    assert!(output.contains("Imported 0 files"));
+    assert_eq!(decrypt(to.path(), path), "<article>Edited</article>");
+}
+
+#[test]
+fn rejects_invalid_puzzles() {
+    let dir = TempDir::new().unwrap();
+    for args in [
+        &["wait", "2025", "40"][..],
+        &["wait", "2025", "0"],
+        &["download", "2025", "300"],
+        &["submit", "99999", "1"],
+        &["submit", "2025", "1", "3", "42"],
+        &["show", "2025", "1", "0"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not in"));
+    }
+}
@@ -0,0 +1,402 @@
+//! A small stand-in for the Advent of Code website, serving just enough of the site for the API
+//! client and the CLI to be exercised offline.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    thread,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+pub const SESSION: &str = "mock-session";
+
+const STYLESHEET: &str = "body { background: #0f0f23; color: #cccccc; }";
+
+/// A font that is not valid UTF-8, to check that binary assets are kept intact.
+pub const FONT: &[u8] = &[0x77, 0x4f, 0x46, 0x32, 0x00, 0xff, 0xfe, 0x80];
+const STYLESHEET_ETAG: &str = "\"style-31\"";
+
+const USER: &str = "Mock User";
+
+const LOCKED: &str = "Please don't repeatedly request this endpoint before it unlocks! The calendar \
+    countdown is synchronized with the server time; the link will be enabled on the calendar the \
+    instant this puzzle becomes available.\n";
+
+const LOGGED_OUT: &str = "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n";
+
+#[derive(Clone, Debug)]
+pub struct Day {
+    pub title: String,
+    pub descriptions: [String; 2],
+    pub input: String,
+    pub answers: [String; 2],
+}
+
+impl Day {
+    /// The first day of 2025, with the example as input so the registered solver can answer it.
+    #[must_use]
+    pub fn example() -> Self {
+        let input = "L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82\n";
+        Self {
+            title: "Secret Entrance".to_string(),
+            descriptions: [
+                format!(
+                    "<p>The dial starts by pointing at <code>50</code>.</p>\
+                     <pre><code>{input}</code></pre>\
+                     <p>The dial points at zero <code><em>3</em></code> times.</p>"
+                ),
+                "<p>Count every click that passes zero, giving <code><em>6</em></code>.</p>"
+                    .to_string(),
+            ],
+            input: input.to_string(),
+            answers: ["3".to_string(), "6".to_string()],
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    days: HashMap<(u32, u32), Day>,
+    locked: HashSet<(u32, u32)>,
+    leaderboards: HashMap<(u32, u64), String>,
+    solved: HashMap<(u32, u32), u32>,
+    rate_limit: Option<u64>,
+    failures: u32,
+    requests: Vec<String>,
+    /// The requests that carried the session cookie, formatted like `requests`.
+    authenticated: Vec<String>,
+}
+
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    #[must_use]
+    pub fn start() -> Self {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let handler_state = Arc::clone(&state);
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                handle(&handler_state, request);
+            }
+        });
+
+        Self { url, state }
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn add_day(&self, year: u32, day: u32, puzzle: Day) {
+        self.state.lock().unwrap().days.insert((year, day), puzzle);
+    }
+
+    pub fn add_leaderboard(&self, year: u32, id: u64, json: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .leaderboards
+            .insert((year, id), json.to_string());
+    }
+
+    /// Makes a day answer as if it has not unlocked yet.
+    pub fn lock_day(&self, year: u32, day: u32) {
+        self.state.lock().unwrap().locked.insert((year, day));
+    }
+
+    pub fn set_solved(&self, year: u32, day: u32, levels: u32) {
+        self.state
+            .lock()
+            .unwrap()
+            .solved
+            .insert((year, day), levels);
+    }
+
+    /// Answers every submission with a rate-limit verdict with the given seconds left to wait.
+    pub fn rate_limit(&self, seconds: Option<u64>) {
+        self.state.lock().unwrap().rate_limit = seconds;
+    }
+
+    /// Answers the next requests with a server error.
+    pub fn fail_next(&self, count: u32) {
+        self.state.lock().unwrap().failures = count;
+    }
+
+    /// Every request received so far, formatted as `METHOD /path`.
+    #[must_use]
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// The requests received so far that carried the session cookie.
+    #[must_use]
+    pub fn authenticated_requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().authenticated.clone()
+    }
+}
+
+fn handle(state: &Mutex<State>, mut request: Request) {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body).unwrap();
+
+    let path = request.url().split('?').next().unwrap().to_string();
+    let logged_in = request.headers().iter().any(|header| {
+        header.field.equiv("Cookie") && header.value.as_str() == format!("session={SESSION}")
+    });
+    let revalidated = request.headers().iter().any(|header| {
+        header.field.equiv("If-None-Match") && header.value.as_str() == STYLESHEET_ETAG
+    });
+
+    let mut state = state.lock().unwrap();
+    let line = format!("{} {path}", request.method().as_str());
+    if logged_in {
+        state.authenticated.push(line.clone());
+    }
+    state.requests.push(line);
+
+    if *request.method() == Method::Get && path == "/static/font.woff2" {
+        drop(state);
+        let header = Header::from_bytes("Content-Type", "font/woff2").unwrap();
+        let response = Response::from_data(FONT).with_header(header);
+        request.respond(response).unwrap();
+        return;
+    }
+
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+    let (status, content_type, text) = match (request.method(), segments.as_slice()) {
+        _ if state.failures > 0 => {
+            state.failures -= 1;
+            (500, "text/plain", "Internal Server Error".to_string())
+        }
+        (Method::Get, ["static", "style.css"]) if revalidated => (304, "text/css", String::new()),
+        (Method::Get, ["static", "style.css"]) => (200, "text/css", STYLESHEET.to_string()),
+        (Method::Get, ["settings"]) => (200, "text/html", settings_page(logged_in)),
+        (Method::Get, ["events"]) => (200, "text/html", events_page(&state)),
+        (Method::Get, [year]) => match year.parse() {
+            Ok(year) => (200, "text/html", calendar_page(&state, year, logged_in)),
+            Err(_) => not_found(),
+        },
+        (Method::Get, [year, "day", day]) => match lookup(&state, year, day) {
+            Ok((key, puzzle)) => (200, "text/html", day_page(&state, key, puzzle, logged_in)),
+            Err(status) => status,
+        },
+        (Method::Get, [year, "day", day, "input"]) => match lookup(&state, year, day) {
+            Ok(_) if !logged_in => (400, "text/plain", LOGGED_OUT.to_string()),
+            Ok((_, puzzle)) => (200, "text/plain", puzzle.input.clone()),
+            Err(status) => status,
+        },
+        (Method::Get, [year, "leaderboard", "private", "view", file]) => {
+            let key = year
+                .parse()
+                .ok()
+                .zip(file.strip_suffix(".json").and_then(|id| id.parse().ok()));
+            match key.and_then(|key| state.leaderboards.get(&key)) {
+                Some(json) if logged_in => (200, "application/json", json.clone()),
+                _ => not_found(),
+            }
+        }
+        (Method::Post, [year, "day", day, "answer"]) => match lookup(&state, year, day) {
+            Ok(_) if !logged_in => (400, "text/plain", LOGGED_OUT.to_string()),
+            Ok((key, puzzle)) => {
+                let puzzle = puzzle.clone();
+                (200, "text/html", answer(&mut state, key, &puzzle, &body))
+            }
+            Err(status) => status,
+        },
+        _ => not_found(),
+    };
+    drop(state);
+
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    let mut response = Response::from_string(text)
+        .with_status_code(status)
+        .with_header(header);
+    if content_type == "text/css" {
+        response.add_header(Header::from_bytes("ETag", STYLESHEET_ETAG).unwrap());
+    }
+    request.respond(response).unwrap();
+}
+
+type Status = (u16, &'static str, String);
+
+fn not_found() -> Status {
+    (404, "text/html", "404 Not Found".to_string())
+}
+
+fn lookup<'a>(state: &'a State, year: &str, day: &str) -> Result<((u32, u32), &'a Day), Status> {
+    let (Ok(year), Ok(day)) = (year.parse(), day.parse()) else {
+        return Err(not_found());
+    };
+    if state.locked.contains(&(year, day)) {
+        return Err((404, "text/plain", LOCKED.to_string()));
+    }
+    state
+        .days
+        .get(&(year, day))
+        .map(|puzzle| ((year, day), puzzle))
+        .ok_or_else(not_found)
+}
+
+fn page(title: &str, main: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en-us\">\n<head>\n<meta charset=\"utf-8\"/>\n\
+         <title>{title}</title>\n\
+         <link rel=\"stylesheet\" type=\"text/css\" href=\"/static/style.css?31\"/>\n\
+         <link rel=\"stylesheet alternate\" type=\"text/css\" href=\"/static/highcontrast.css?1\" title=\"High Contrast\"/>\n\
+         </head>\n<body>\n<main>\n{main}\n</main>\n</body>\n</html>\n"
+    )
+}
+
+fn settings_page(logged_in: bool) -> String {
+    let header = if logged_in {
+        format!(
+            "<header><div class=\"user\">{USER} <span class=\"star-count\">0*</span></div></header>"
+        )
+    } else {
+        "<header><div><a href=\"/auth/login\">[Log In]</a></div></header>".to_string()
+    };
+    page("Settings - Advent of Code", &header)
+}
+
+fn events_page(state: &State) -> String {
+    let mut years: Vec<_> = state.days.keys().map(|(year, _)| *year).collect();
+    years.sort_unstable();
+    years.dedup();
+
+    let events: String = years
+        .iter()
+        .rev()
+        .map(|year| {
+            let stars: u32 = state
+                .solved
+                .iter()
+                .filter(|((y, _), _)| y == year)
+                .map(|(_, levels)| levels)
+                .sum();
+            format!(
+                "<div class=\"eventlist-event\"><a href=\"/{year}\">[{year}]</a> \
+                 <span class=\"star-count\">{stars}*</span></div>\n"
+            )
+        })
+        .collect();
+
+    page("Events - Advent of Code", &events)
+}
+
+fn calendar_page(state: &State, year: u32, logged_in: bool) -> String {
+    let mut days: Vec<_> = state
+        .days
+        .keys()
+        .filter(|(y, _)| *y == year)
+        .map(|(_, day)| *day)
+        .collect();
+    days.sort_unstable();
+
+    let links: String = days
+        .iter()
+        .map(|day| {
+            let solved = if logged_in {
+                state.solved.get(&(year, *day)).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            let (label, class) = match solved {
+                0 => (String::new(), ""),
+                1 => (", one star".to_string(), " calendar-complete"),
+                _ => (", two stars".to_string(), " calendar-verycomplete"),
+            };
+            format!(
+                "<a aria-label=\"Day {day}{label}\" href=\"/{year}/day/{day}\" \
+                 class=\"calendar-day{day}{class}\">{day}</a>\n"
+            )
+        })
+        .collect();
+
+    page(
+        &format!("Advent of Code {year}"),
+        &format!("<pre class=\"calendar\">\n{links}</pre>"),
+    )
+}
+
+fn day_page(state: &State, (year, day): (u32, u32), puzzle: &Day, logged_in: bool) -> String {
+    let solved = if logged_in {
+        state.solved.get(&(year, day)).copied().unwrap_or(0)
+    } else {
+        0
+    };
+
+    let mut main = format!(
+        "<article class=\"day-desc\"><h2>--- Day {day}: {} ---</h2>{}</article>\n",
+        puzzle.title, puzzle.descriptions[0]
+    );
+    if solved >= 1 {
+        main.push_str(&format!(
+            "<p>Your puzzle answer was <code>{}</code>.</p>\
+             <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>{}</article>\n",
+            puzzle.answers[0], puzzle.descriptions[1]
+        ));
+    }
+    if solved >= 2 {
+        main.push_str(&format!(
+            "<p>Your puzzle answer was <code>{}</code>.</p>\
+             <p class=\"day-success\">Both parts of this puzzle are complete! \
+             They provide two gold stars: **</p>\n",
+            puzzle.answers[1]
+        ));
+    }
+
+    page(&format!("Day {day} - Advent of Code {year}"), &main)
+}
+
+fn answer(state: &mut State, key: (u32, u32), puzzle: &Day, body: &str) -> String {
+    let form: HashMap<_, _> = body
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+    let level: u32 = form.get("level").and_then(|l| l.parse().ok()).unwrap_or(0);
+    let given = form.get("answer").copied().unwrap_or_default();
+    let solved = state.solved.get(&key).copied().unwrap_or(0);
+
+    let message = if let Some(seconds) = state.rate_limit {
+        format!(
+            "You gave an answer too recently; you have to wait after submitting an answer \
+             before trying again.  You have {}m {}s left to wait.",
+            seconds / 60,
+            seconds % 60
+        )
+    } else if level != solved + 1 {
+        "You don't seem to be solving the right level.  Did you already complete it?".to_string()
+    } else {
+        let expected = &puzzle.answers[level as usize - 1];
+        if given == expected {
+            state.solved.insert(key, level);
+            "That's the right answer!  You are <span class=\"day-success\">one gold star</span> \
+             closer to decorating the North Pole."
+                .to_string()
+        } else {
+            let hint = match (given.parse::<i64>(), expected.parse::<i64>()) {
+                (Ok(given), Ok(expected)) if given > expected => "  your answer is too high.",
+                (Ok(given), Ok(expected)) if given < expected => "  your answer is too low.",
+                _ => "",
+            };
+            format!(
+                "That's not the right answer;{hint}  If you're stuck, make sure you're using the \
+                 full input data.  Please wait one minute before trying again."
+            )
+        }
+    };
+
+    page(
+        "Day - Advent of Code",
+        &format!("<article><p>{message}</p></article>"),
+    )
+}
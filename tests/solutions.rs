@@ -1,13 +1,16 @@
 use aoc::{
     PuzzleId,
-    storage::{self},
+    storage::{EncryptedStore, PuzzleStore},
 };
-use std::env;
+use std::{env, path::Path};
 
 fn run_test(year: u32, day: u32, part: u32) {
     let test_id = PuzzleId::new(year, day, part);
     let password = env::var("AOC_KEY").unwrap();
-    let puzzle = storage::get(&test_id, &password).unwrap();
+    let puzzle = EncryptedStore::unlock(Path::new(env!("CARGO_MANIFEST_DIR")), &password)
+        .unwrap_or_else(|e| panic!("{e}"))
+        .get(&test_id)
+        .unwrap();
     for test in puzzle.tests {
         assert_eq!(
             aoc::solve(year, day, part, &test.input),